@@ -4,12 +4,29 @@ use std::{
 };
 
 use common::{
-    data::cards::CardID,
+    data::{cards::CardID, text::sanitize_name},
     protocol::{
-        clientbound::{ClientBoundPacket, PacketResponse, ResponseData},
+        clientbound::{
+            ChatScope,
+            ClientBoundPacket,
+            Combo,
+            PacketResponse,
+            ResponseData,
+            RoundRecord,
+        },
         serverbound::ServerBoundPacket,
-        GameSetting,
+        CzarRotation,
+        EndCondition,
         GameSettings,
+        JudgingMode,
+        LateJoin,
+        Reaction,
+        ReactionTarget,
+        ServerFilter,
+        ServerListEntry,
+        ServerSort,
+        TieBreak,
+        PASSWORD_REQUIRED,
     },
 };
 use uuid::Uuid;
@@ -24,31 +41,65 @@ use crate::{
         add_card_to_hand,
         add_packs,
         add_player,
+        add_round_entry,
         add_server,
         clear_blank_responses,
+        clear_chat,
         clear_hand,
+        clear_invite_from_url,
         clear_player_marks,
+        clear_rejoin_token,
         clear_response_cards,
         clear_servers,
+        combo_html,
         disable_hand,
         disable_start_game,
         enable_hand,
         get_hand_element,
         get_name_input_value,
+        get_server_view,
+        hide_game_end,
+        hide_paused,
+        hide_spectating,
         init_game,
         init_lobby,
+        invite_from_url,
+        mark_game_winner,
+        mark_player_afk,
         mark_player_czar,
+        mark_player_muted,
         mark_player_played,
+        mark_player_voted,
         mark_winner,
         place_blank_response,
+        prompt_password,
+        prompt_write_in,
+        rejoin_token,
         remove_card_from_hand,
         remove_player,
         remove_response,
+        round_entry_html,
+        save_rejoin_token,
+        set_game_summary,
+        set_invite_code,
         set_player_responses,
         set_prompt_card,
+        set_round_history,
+        set_winner_reactions,
+        show_chat_message,
+        show_chat_notice,
+        show_error,
         show_game_end,
+        show_paused,
+        show_reaction,
+        show_response_votes,
+        show_spectating,
+        standing_html,
+        take_chat_input,
         update_player_name,
         update_player_points,
+        update_skip_votes,
+        winner_reaction_html,
     },
     js_events::register_events,
     ws::WebSocket,
@@ -59,6 +110,7 @@ use crate::{
 pub struct Player {
     pub name: String,
     pub points: u32,
+    pub is_bot: bool,
 }
 
 #[derive(Debug)]
@@ -70,12 +122,23 @@ pub enum GameState {
     End,
 }
 
+/// A game the page was opened to join through an invite link
+#[derive(Debug, Clone)]
+pub enum Invite {
+    Code(String),
+    Id(Uuid),
+}
+
 #[derive(Debug)]
 pub enum CachedPacket {
     SelectResponse(ResponseCard, usize),
     SelectRoundWinner,
     JoinGame,
+    JoinInvite(Invite),
+    Rejoin,
+    QuickMatch(HtmlElement),
     CreateServer(HtmlElement),
+    SendChat(ChatScope),
 }
 
 pub struct GameManager {
@@ -96,6 +159,13 @@ pub struct GameManager {
     server_closures: Vec<Closure<dyn FnMut()>>,
     pub settings: GameSettings,
     pub known_packs: Vec<(String, usize, usize)>,
+    pub history: Vec<RoundRecord>,
+    // Joined as soon as we get our id
+    pub pending_invite: Option<Invite>,
+    // Every listed game, whether or not it matches the filter
+    pub servers: Vec<ServerListEntry>,
+    pub server_sort: ServerSort,
+    pub server_filter: ServerFilter,
 }
 
 
@@ -107,6 +177,7 @@ pub fn game_init(socket: WebSocket, packet_receiver: Receiver<ClientBoundPacket>
         player: Player {
             name: name.clone(),
             points: 0,
+            is_bot: false,
         },
         id: Uuid::from_u128(0),
         packet_cache: HashMap::new(),
@@ -124,8 +195,33 @@ pub fn game_init(socket: WebSocket, packet_receiver: Receiver<ClientBoundPacket>
             max_selection_time: None,
             packs: Vec::new(),
             points_to_win: 10,
+            blank_cards: 0,
+            save_write_ins: false,
+            rebooting_the_universe: false,
+            packing_heat: false,
+            judging_mode: JudgingMode::Czar,
+            vote_tie_break: TieBreak::Random,
+            hand_size: 10,
+            end_condition: EndCondition::Points,
+            end_tie_break: TieBreak::AllWin,
+            czar_rotation: CzarRotation::Sequential,
+            pause_timeout: 5,
+            skip_vote_threshold: Some(50),
+            afk_threshold: 2,
+            kick_afk: false,
+            late_join: LateJoin::Allow,
+            late_join_handicap: false,
+            password: None,
+            unlisted: false,
+            title: String::new(),
+            seed: None,
         },
         known_packs: Vec::new(),
+        history: Vec::new(),
+        pending_invite: invite_from_url(),
+        servers: Vec::new(),
+        server_sort: ServerSort::default(),
+        server_filter: ServerFilter::default(),
     }));
 
     init_lobby();
@@ -159,16 +255,52 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
     match packet {
         ClientBoundPacket::SetId(id) => {
             add_player(&manager.player, &id);
-            manager.id = id
+            manager.id = id;
+
+            // Let the lobby know who we are for chat
+            if let Some(packet) = player_name_packet(&manager.player.name) {
+                manager.socket.lock().unwrap().send_packet(&packet).unwrap();
+            }
+
+            // Following an invite link takes priority over going back to an older game
+            if let Some(invite) = manager.pending_invite.take() {
+                join_invite(&mut manager, invite, None);
+            // Try to get back into the game we were in before the connection dropped
+            } else if let Some((game_id, token)) = rejoin_token() {
+                let socket = manager.socket.lock().unwrap();
+                let packet_id = socket
+                    .send_packet_with_id(ServerBoundPacket::Rejoin { game_id, token })
+                    .unwrap();
+                drop(socket);
+                manager.packet_cache.insert(packet_id, CachedPacket::Rejoin);
+            }
         }
+
+        ClientBoundPacket::InviteCode(code) => set_invite_code(&code),
+        ClientBoundPacket::RejoinToken { game_id, token } =>
+            save_rejoin_token(&game_id.to_string(), &token.to_string()),
         ClientBoundPacket::AddPlayer {
             id,
             name,
             is_host,
+            is_bot,
             points,
         } => {
-            if id != manager.id {
-                let player = Player { name, points };
+            if id == manager.id {
+                // We're in the game rather than just watching it
+                hide_spectating();
+
+                // Without a valid name of our own we go by the one the game gave us
+                if sanitize_name(&manager.player.name).is_err() {
+                    update_player_name(&id.to_string(), &name);
+                    manager.player.name = name;
+                }
+            } else {
+                let player = Player {
+                    name,
+                    points,
+                    is_bot,
+                };
 
                 let id = id;
 
@@ -194,10 +326,13 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
                 clear_player_marks(&id.to_string())
             }
             clear_player_marks(&manager.id.to_string());
+            update_skip_votes(0, 0);
 
-            manager.is_czar = manager.id == czar;
+            manager.is_czar = Some(manager.id) == czar;
 
-            mark_player_czar(&czar.to_string());
+            if let Some(czar) = czar {
+                mark_player_czar(&czar.to_string());
+            }
             if !manager.is_czar {
                 enable_hand();
             } else {
@@ -230,10 +365,12 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
             clear_response_cards();
 
             manager.state = GameState::PickResponse(responses.clone());
+            let own_id = manager.id;
             drop(manager);
 
             responses.iter().for_each(|(id, responses)| {
-                let element = set_player_responses(id, responses);
+                // Everyone can react to the submissions but their own
+                let element = set_player_responses(id, responses, *id != own_id);
                 set_response_onclick(element, *id, manager_arc.clone())
             });
             // clear blank responses to just get any somehow left out
@@ -243,13 +380,20 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
 
         ClientBoundPacket::StartGame => {
             update_player_points(&manager.id.to_string(), manager.player.points);
+            manager.history.clear();
+            set_round_history(&[]);
+            set_winner_reactions(&[]);
         }
 
-        ClientBoundPacket::UpdatePlayerName { id, name } =>
-            if id != manager.id {
-                update_player_name(&id.to_string(), &name);
-                manager.others.get_mut(&id).unwrap().name = name;
-            },
+        ClientBoundPacket::UpdatePlayerName { id, name } => {
+            update_player_name(&id.to_string(), &name);
+            // The game can change our name if someone else already has it
+            if id == manager.id {
+                manager.player.name = name;
+            } else if let Some(player) = manager.others.get_mut(&id) {
+                player.name = name;
+            }
+        }
 
         ClientBoundPacket::RemovePlayer { id, new_host } => {
             remove_player(&id.to_string());
@@ -279,33 +423,90 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
             }
         },
 
-        ClientBoundPacket::DisplayWinner { winner, end_game } => {
-            mark_winner(&winner.to_string());
+        ClientBoundPacket::DisplayWinner { winner, end_game } =>
+            display_winners(&mut manager, &[winner], end_game),
 
-            if end_game {
-                manager.state = GameState::End;
-                disable_hand();
-                show_game_end();
-                clear_hand();
-            } else {
-                if winner == manager.id {
-                    manager.player.points += 1;
-                    update_player_points(&manager.id.to_string(), manager.player.points);
-                } else {
-                    let player = manager.others.get_mut(&winner).unwrap();
-                    player.points += 1;
-                    update_player_points(&winner.to_string(), player.points);
-                }
+        ClientBoundPacket::PlayerVoted(id) => mark_player_voted(&id.to_string()),
+
+        ClientBoundPacket::VoteResults {
+            votes,
+            winners,
+            end_game,
+        } => {
+            for (id, count) in votes {
+                show_response_votes(&id.to_string(), count);
             }
+
+            display_winners(&mut manager, &winners, end_game);
         }
 
-        ClientBoundPacket::SettingUpdate(settings) => match settings {
-            GameSetting::AddPack(pack) => manager.settings.packs.push(pack),
-            GameSetting::MaxPlayers(max_players) => manager.settings.max_players = max_players,
-            GameSetting::MaxSelectionTime(time) => manager.settings.max_selection_time = time,
-            GameSetting::PointsToWin(points) => manager.settings.points_to_win = points,
-            GameSetting::RemovePack(pack) => manager.settings.packs.retain(|i| i != &pack),
-        },
+        ClientBoundPacket::RoundHistory(history) => {
+            let entries = history
+                .iter()
+                .map(|round| round_entry_html(round, |id| player_name(&manager, id)))
+                .collect::<Vec<_>>();
+            set_round_history(&entries);
+            manager.history = history;
+        }
+
+        ClientBoundPacket::RoundRecorded(round) => {
+            add_round_entry(&round_entry_html(&round, |id| player_name(&manager, id)));
+            let winners = round
+                .submissions
+                .iter()
+                .filter(|submission| {
+                    round.winners.contains(&submission.player) && submission.player != manager.id
+                })
+                .map(|submission| {
+                    winner_reaction_html(
+                        &submission.player,
+                        &player_name(&manager, &submission.player),
+                        &submission.responses,
+                    )
+                })
+                .collect::<Vec<_>>();
+            set_winner_reactions(&winners);
+            manager.history.push(round);
+        }
+
+        ClientBoundPacket::GameSummary(summary) => {
+            let standings = summary
+                .standings
+                .iter()
+                .map(standing_html)
+                .collect::<Vec<_>>();
+            let summary_combo_html = |combo: &Combo| {
+                let name = summary
+                    .standings
+                    .iter()
+                    .find(|stats| stats.id == combo.submission.player)
+                    .map(|stats| stats.name.clone())
+                    .unwrap_or_else(|| player_name(&manager, &combo.submission.player));
+                combo_html(combo, &name)
+            };
+            let combos = summary
+                .best_combos
+                .iter()
+                .map(summary_combo_html)
+                .collect::<Vec<_>>();
+            let crowd_favorite = summary.crowd_favorite.as_ref().map(summary_combo_html);
+            set_winner_reactions(&[]);
+            set_game_summary(&standings, &combos, crowd_favorite);
+        }
+
+        ClientBoundPacket::GameWinners(winners) =>
+            for winner in winners {
+                mark_game_winner(&winner.to_string());
+            },
+
+        ClientBoundPacket::SettingUpdate(setting) => manager.settings.apply(&setting),
+
+        ClientBoundPacket::PauseState { paused, resumes_in } =>
+            if paused {
+                show_paused(resumes_in as u32);
+            } else {
+                hide_paused();
+            },
 
         ClientBoundPacket::Ack {
             packet_id,
@@ -335,27 +536,65 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
                             manager.response_closures = Vec::new();
                         }
 
-                        CachedPacket::JoinGame => {
+                        CachedPacket::SendChat(_) => {}
+
+                        CachedPacket::JoinGame | CachedPacket::JoinInvite(_) => {
+                            clear_invite_from_url();
                             manager.server_closures = Vec::new();
                             let socket = manager.socket.lock().unwrap();
+                            if let Some(packet) = player_name_packet(&manager.player.name) {
+                                socket.send_packet(&packet).unwrap();
+                            }
+                            // Catch up on the rounds played before we joined
                             socket
-                                .send_packet(&ServerBoundPacket::SetPlayerName(
-                                    manager.player.name.clone(),
-                                ))
+                                .send_packet(&ServerBoundPacket::RequestHistory)
                                 .unwrap();
                             disable_start_game();
                             init_game();
                             clear_servers();
                         }
 
-                        CachedPacket::CreateServer(ele) => {
-                            manager.state = GameState::Waiting;
+                        CachedPacket::Rejoin => {
+                            manager.server_closures = Vec::new();
+                            manager
+                                .socket
+                                .lock()
+                                .unwrap()
+                                .send_packet(&ServerBoundPacket::RequestHistory)
+                                .unwrap();
+                            disable_start_game();
+                            init_game();
+                            clear_servers();
+                        }
+
+                        CachedPacket::QuickMatch(ele) => {
+                            manager.server_closures = Vec::new();
                             let socket = manager.socket.lock().unwrap();
+                            if let Some(packet) = player_name_packet(&manager.player.name) {
+                                socket.send_packet(&packet).unwrap();
+                            }
                             socket
-                                .send_packet(&ServerBoundPacket::SetPlayerName(
-                                    manager.player.name.clone(),
-                                ))
+                                .send_packet(&ServerBoundPacket::RequestHistory)
                                 .unwrap();
+                            drop(socket);
+                            init_game();
+                            clear_servers();
+
+                            // Nothing open fit, so the server made a new game for us to host
+                            if manager.host == manager.id {
+                                manager.state = GameState::Waiting;
+                                ele.set_hidden(false);
+                            } else {
+                                disable_start_game();
+                            }
+                        }
+
+                        CachedPacket::CreateServer(ele) => {
+                            manager.state = GameState::Waiting;
+                            let socket = manager.socket.lock().unwrap();
+                            if let Some(packet) = player_name_packet(&manager.player.name) {
+                                socket.send_packet(&packet).unwrap();
+                            }
                             init_game();
                             ele.set_hidden(false);
                         }
@@ -370,31 +609,210 @@ fn game_loop(manager_arc: Arc<Mutex<GameManager>>, packet: ClientBoundPacket) {
                         None => return,
                     };
 
+                    // Let the player know why they couldn't get into a game
+                    match (&packet, &response) {
+                        (
+                            CachedPacket::JoinInvite(invite),
+                            PacketResponse::RejectedWithReason(reason),
+                        ) if reason == PASSWORD_REQUIRED => {
+                            // Invite links don't say whether the game has a password
+                            if let Some(password) = prompt_password() {
+                                join_invite(&mut manager, invite.clone(), Some(password));
+                            } else {
+                                clear_invite_from_url();
+                            }
+                        }
+                        (
+                            CachedPacket::JoinGame | CachedPacket::JoinInvite(_),
+                            PacketResponse::RejectedWithReason(reason),
+                        ) => {
+                            clear_invite_from_url();
+                            show_error(reason);
+                        }
+                        (
+                            CachedPacket::QuickMatch(_),
+                            PacketResponse::RejectedWithReason(reason),
+                        ) => show_error(reason),
+                        (
+                            CachedPacket::SendChat(scope),
+                            PacketResponse::RejectedWithReason(reason),
+                        ) => show_chat_notice(*scope, reason),
+                        _ => {}
+                    }
+
                     revert_packet(&mut manager, packet);
                 }
             }
         }
 
+        ClientBoundPacket::UpdatePoints { id, points } => {
+            if id == manager.id {
+                manager.player.points = points;
+            } else if let Some(player) = manager.others.get_mut(&id) {
+                player.points = points;
+            }
+
+            update_player_points(&id.to_string(), points);
+        }
+
+        ClientBoundPacket::NewHand(responses) => {
+            clear_hand();
+            manager.hand_closures = HashMap::new();
+            manager.hand = responses
+                .iter()
+                .map(|card| ResponseCard {
+                    text: card.text.clone(),
+                    id: card.id,
+                })
+                .collect();
+
+            drop(manager);
+
+            for card in responses.iter() {
+                let element = add_card_to_hand(&card);
+
+                set_hand_onclick(element, card.id, manager_arc.clone())
+            }
+        }
+
         ClientBoundPacket::CancelRound => {
             clear_response_cards();
         }
 
+        ClientBoundPacket::SkipVotes { votes, needed } =>
+            update_skip_votes(votes as u32, needed as u32),
+
+        ClientBoundPacket::PlayerAfk { id, afk } => mark_player_afk(&id.to_string(), afk),
+
+        ClientBoundPacket::Spectating { joining_next_round } =>
+            if joining_next_round {
+                show_spectating("You'll be dealt in when the next round starts");
+            } else {
+                show_spectating("You're spectating this game");
+            },
+
+        ClientBoundPacket::Kicked(reason) => {
+            manager.state = GameState::Lobby;
+            hide_game_end();
+            hide_paused();
+            hide_spectating();
+            init_lobby();
+            show_error(&reason);
+        }
+
         ClientBoundPacket::ServerList { servers } => match manager.state {
             GameState::Lobby => {
+                manager.servers = servers;
                 drop(manager);
-                clear_servers();
-                for server in servers {
-                    let ele = add_server(&server.0, &server.1, server.2, server.3);
-                    set_server_onclick(ele, server.0, manager_arc.clone())
-                }
+                render_servers(manager_arc);
             }
             _ => console_warn!("Got ServerList while not in Lobby"),
         },
 
+        ClientBoundPacket::ServerAdded(server) | ClientBoundPacket::ServerUpdated(server) => {
+            match manager
+                .servers
+                .iter_mut()
+                .find(|entry| entry.id == server.id)
+            {
+                Some(entry) => *entry = server,
+                None => manager.servers.push(server),
+            }
+
+            if let GameState::Lobby = manager.state {
+                drop(manager);
+                render_servers(manager_arc);
+            }
+        }
+
+        ClientBoundPacket::ServerRemoved(id) => {
+            manager.servers.retain(|entry| entry.id != id);
+
+            if let GameState::Lobby = manager.state {
+                drop(manager);
+                render_servers(manager_arc);
+            }
+        }
+
         ClientBoundPacket::CardPacks(packs) => {
             manager.known_packs.extend(packs.clone());
             add_packs(packs);
         }
+
+        ClientBoundPacket::Chat { scope, message } => show_chat_message(scope, &message),
+
+        ClientBoundPacket::ChatHistory { scope, messages } => {
+            clear_chat(scope);
+            for message in messages.iter() {
+                show_chat_message(scope, message);
+            }
+        }
+
+        ClientBoundPacket::Reacted { target, reaction } => {
+            let element_id = match target {
+                ReactionTarget::Submission(id) => format!("player-{}-responses", id),
+                ReactionTarget::Winner(id) => format!("winner-{}", id),
+            };
+            show_reaction(&element_id, reaction.emoji());
+        }
+
+        ClientBoundPacket::ChatMuted { id, muted } => {
+            mark_player_muted(&id.to_string(), muted);
+            if id == manager.id {
+                show_chat_notice(
+                    ChatScope::Game,
+                    if muted {
+                        "The host muted you"
+                    } else {
+                        "The host unmuted you"
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// The name of a player, or a placeholder if they've left the game
+/// The packet setting our name, if the name is one the server will accept
+fn player_name_packet(name: &str) -> Option<ServerBoundPacket> {
+    sanitize_name(name)
+        .ok()
+        .map(ServerBoundPacket::SetPlayerName)
+}
+
+fn player_name(manager: &GameManager, id: &Uuid) -> String {
+    if *id == manager.id {
+        manager.player.name.clone()
+    } else {
+        manager
+            .others
+            .get(id)
+            .map(|player| player.name.clone())
+            .unwrap_or_else(|| "Someone who left".to_owned())
+    }
+}
+
+fn display_winners(manager: &mut GameManager, winners: &[Uuid], end_game: bool) {
+    for winner in winners {
+        mark_winner(&winner.to_string());
+    }
+
+    if end_game {
+        manager.state = GameState::End;
+        disable_hand();
+        show_game_end();
+        clear_hand();
+    } else {
+        for &winner in winners {
+            if winner == manager.id {
+                manager.player.points += 1;
+                update_player_points(&manager.id.to_string(), manager.player.points);
+            } else {
+                let player = manager.others.get_mut(&winner).unwrap();
+                player.points += 1;
+                update_player_points(&winner.to_string(), player.points);
+            }
+        }
     }
 }
 
@@ -408,6 +826,8 @@ fn revert_packet(manager: &mut GameManager, packet: CachedPacket) {
                 _ => GameState::MakeResponse(1),
             }
         }
+        // The game is gone or already moved on without us
+        CachedPacket::Rejoin => clear_rejoin_token(),
         _ => {}
     }
 }
@@ -446,11 +866,22 @@ fn hand_click(element: HtmlElement, card_id: CardID, manager: Arc<Mutex<GameMana
     if !manager.is_czar {
         match manager.state {
             GameState::MakeResponse(picks_left) => {
+                let packet = if card_id.is_blank() {
+                    // Blank cards are only played once the player has written something on them
+                    match prompt_write_in() {
+                        Some(text) => ServerBoundPacket::SelectWriteIn {
+                            card: card_id,
+                            text,
+                        },
+                        None => return,
+                    }
+                } else {
+                    ServerBoundPacket::SelectResponse(card_id)
+                };
+
                 // Send card to server
                 let socket = manager.socket.lock().unwrap();
-                let id = socket
-                    .send_packet_with_id(ServerBoundPacket::SelectResponse(card_id))
-                    .unwrap();
+                let id = socket.send_packet_with_id(packet).unwrap();
                 drop(socket);
 
                 // Cache the card in case of revert
@@ -496,7 +927,24 @@ fn set_response_onclick(element: HtmlElement, user_id: Uuid, manager: Arc<Mutex<
 
 fn response_click(user_id: Uuid, manager: Arc<Mutex<GameManager>>) {
     let mut manager = manager.lock().unwrap();
-    if manager.is_czar {
+    if manager.settings.judging_mode == JudgingMode::Democracy {
+        match &manager.state {
+            // Players can't vote for their own responses
+            GameState::PickResponse(_) if user_id != manager.id => {
+                let socket = manager.socket.lock().unwrap();
+                let packet_id = socket
+                    .send_packet_with_id(ServerBoundPacket::VoteForResponse(user_id))
+                    .unwrap();
+                drop(socket);
+
+                manager
+                    .packet_cache
+                    .insert(packet_id, CachedPacket::SelectRoundWinner);
+                manager.state = GameState::Waiting;
+            }
+            _ => {}
+        }
+    } else if manager.is_czar {
         match &manager.state {
             GameState::PickResponse(_) => {
                 let socket = manager.socket.lock().unwrap();
@@ -515,11 +963,55 @@ fn response_click(user_id: Uuid, manager: Arc<Mutex<GameManager>>) {
     }
 }
 
-fn set_server_onclick(element: HtmlElement, server_id: Uuid, manager: Arc<Mutex<GameManager>>) {
+/// Redraws the server list in the order and with the filter the player picked
+fn render_servers(manager_arc: Arc<Mutex<GameManager>>) {
+    let mut manager = manager_arc.lock().unwrap();
+    let mut servers = manager
+        .servers
+        .iter()
+        .filter(|server| manager.server_filter.matches(server))
+        .cloned()
+        .collect::<Vec<_>>();
+    servers.sort_by(|a, b| manager.server_sort.compare(a, b));
+    manager.server_closures = Vec::new();
+    drop(manager);
+
+    clear_servers();
+    for server in servers {
+        let ele = add_server(&server);
+        set_server_onclick(ele, server.id, server.has_password, manager_arc.clone());
+    }
+}
+
+/// Asks the server for the list of games again using the sort and filter the player picked
+pub fn refresh_servers(manager_arc: Arc<Mutex<GameManager>>) {
+    let (sort, filter) = get_server_view();
+
+    let mut manager = manager_arc.lock().unwrap();
+    manager.server_sort = sort;
+    manager.server_filter = filter.clone();
+    manager
+        .socket
+        .lock()
+        .unwrap()
+        .send_packet(&ServerBoundPacket::RefreshServerList { sort, filter })
+        .unwrap();
+    drop(manager);
+
+    render_servers(manager_arc);
+}
+
+fn set_server_onclick(
+    element: HtmlElement,
+    server_id: Uuid,
+    has_password: bool,
+    manager: Arc<Mutex<GameManager>>,
+) {
     console_log!("setting onclick for {}", server_id);
     let server_manager = manager.clone();
-    let server_closure =
-        Closure::<dyn FnMut()>::new(move || server_click(server_id, server_manager.clone()));
+    let server_closure = Closure::<dyn FnMut()>::new(move || {
+        server_click(server_id, has_password, server_manager.clone())
+    });
 
     element.set_onclick(Some(server_closure.as_ref().unchecked_ref()));
 
@@ -528,11 +1020,96 @@ fn set_server_onclick(element: HtmlElement, server_id: Uuid, manager: Arc<Mutex<
     manager.server_closures.push(server_closure);
 }
 
-fn server_click(server_id: Uuid, manager: Arc<Mutex<GameManager>>) {
+pub fn send_reaction(
+    manager_arc: Arc<Mutex<GameManager>>,
+    target: ReactionTarget,
+    reaction: Reaction,
+) {
+    let manager = manager_arc.lock().unwrap();
+    manager
+        .socket
+        .lock()
+        .unwrap()
+        .send_packet(&ServerBoundPacket::React { target, reaction })
+        .unwrap();
+}
+
+/// Sends whatever's in a chat box. In games the host can also type `/mute <name>` or
+/// `/unmute <name>`.
+pub fn send_chat(manager_arc: Arc<Mutex<GameManager>>, scope: ChatScope) {
+    let text = take_chat_input(scope);
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+
+    let mut manager = manager_arc.lock().unwrap();
+
+    let command = text
+        .strip_prefix("/mute ")
+        .map(|name| (name, true))
+        .or_else(|| text.strip_prefix("/unmute ").map(|name| (name, false)));
+    if let (ChatScope::Game, Some((name, muted))) = (scope, command) {
+        let name = name.trim();
+        let id = manager
+            .others
+            .iter()
+            .find(|(_, player)| player.name.eq_ignore_ascii_case(name))
+            .map(|(id, _)| *id);
+
+        match id {
+            Some(id) => manager
+                .socket
+                .lock()
+                .unwrap()
+                .send_packet(&ServerBoundPacket::MuteChat { id, muted })
+                .unwrap(),
+            None => show_chat_notice(scope, &format!("Nobody here is named {}", name)),
+        }
+        return;
+    }
+
+    let socket = manager.socket.lock().unwrap();
+    let id = socket
+        .send_packet_with_id(ServerBoundPacket::SendChat(text.to_owned()))
+        .unwrap();
+    drop(socket);
+    manager
+        .packet_cache
+        .insert(id, CachedPacket::SendChat(scope));
+}
+
+fn join_invite(manager: &mut GameManager, invite: Invite, password: Option<String>) {
+    let packet = match invite.clone() {
+        Invite::Code(code) => ServerBoundPacket::JoinByCode { code, password },
+        Invite::Id(id) => ServerBoundPacket::JoinGame { id, password },
+    };
+
+    let socket = manager.socket.lock().unwrap();
+    let id = socket.send_packet_with_id(packet).unwrap();
+    drop(socket);
+    manager
+        .packet_cache
+        .insert(id, CachedPacket::JoinInvite(invite));
+}
+
+fn server_click(server_id: Uuid, has_password: bool, manager: Arc<Mutex<GameManager>>) {
+    let password = if has_password {
+        match prompt_password() {
+            Some(password) => Some(password),
+            None => return,
+        }
+    } else {
+        None
+    };
+
     let mut manager = manager.lock().unwrap();
     let socket = manager.socket.lock().unwrap();
     let id = socket
-        .send_packet_with_id(ServerBoundPacket::JoinGame(server_id))
+        .send_packet_with_id(ServerBoundPacket::JoinGame {
+            id: server_id,
+            password,
+        })
         .unwrap();
     drop(socket);
     manager.packet_cache.insert(id, CachedPacket::JoinGame);
@@ -1,43 +1,77 @@
-use common::protocol::{clientbound::ResponseData, GameSettings};
+use common::{
+    data::{
+        cards::MAX_WRITE_IN_LENGTH,
+        markup::{escape_html, markup_to_html},
+    },
+    protocol::{
+        clientbound::{ChatMessage, ChatScope, Combo, PlayerStats, ResponseData, RoundRecord},
+        is_invite_code,
+        CzarRotation,
+        EndCondition,
+        GamePhase,
+        GameSettings,
+        JudgingMode,
+        LateJoin,
+        Reaction,
+        ServerFilter,
+        ServerListEntry,
+        ServerSort,
+        TieBreak,
+    },
+};
 use js_sys::Array;
 use uuid::Uuid;
 use wasm_bindgen::{prelude::*, JsCast};
-use web_sys::{HtmlElement, HtmlInputElement};
+use web_sys::{Element, Event, HtmlElement, HtmlInputElement, HtmlSelectElement};
 
-use crate::game::{Player, PromptCard};
+use crate::game::{Invite, Player, PromptCard};
 
 static RESPONSE_TEMPLATE: &'static str = include_str!("./templates/white_card.html");
 static PROMPT_TEMPLATE: &'static str = include_str!("./templates/black_card.html");
 static PLAYER_TEMPLATE: &'static str = include_str!("./templates/player.html");
 static PLAYER_RESPONSE_TEMPLATE: &'static str = include_str!("./templates/responses.html");
 static SERVER_TEMPLATE: &'static str = include_str!("./templates/server_entry.html");
+static ROUND_ENTRY_TEMPLATE: &'static str = include_str!("./templates/round_entry.html");
+static STANDING_TEMPLATE: &'static str = include_str!("./templates/standing.html");
+static COMBO_TEMPLATE: &'static str = include_str!("./templates/combo.html");
+
+static BLANK_CARD_TEXT: &'static str = "<i>Write your own answer</i>";
 
 // Template variables
 // $ID the id of the card
 // $TEXT the text of the card
 pub fn response_card_html(card: &ResponseData) -> String {
+    // Blank cards in the hand have no text until they're played
+    let text = if card.id.is_blank() && card.text.is_empty() {
+        BLANK_CARD_TEXT.to_owned()
+    } else {
+        markup_to_html(&card.text)
+    };
+
     RESPONSE_TEMPLATE
         .replace(
             "$ID",
             &format!("{}_{}", card.id.pack_number, card.id.card_number),
         )
-        .replace("$TEXT", &card.text)
+        .replace("$TEXT", &text)
 }
 
 // Template variables
 // $TEXT the text of the card
 pub fn prompt_card_html(card: &PromptCard) -> String {
-    PROMPT_TEMPLATE.replace("$TEXT", &card.text)
+    PROMPT_TEMPLATE.replace("$TEXT", &markup_to_html(&card.text))
 }
 
 // Template variables
 // $ID the internal id of the user
+// $CLASS extra classes for the player entry
 // $NAME the name of the user
 // $POINTS the points of the user
 pub fn player_html(player: &Player, id: &Uuid) -> String {
     PLAYER_TEMPLATE
         .replace("$ID", &format!("{}", id))
-        .replace("$NAME", &player.name)
+        .replace("$CLASS", if player.is_bot { "bot" } else { "" })
+        .replace("$NAME", &escape_html(&player.name))
         .replace("$POINTS", &player.points.to_string())
 }
 
@@ -47,12 +81,215 @@ pub fn player_response_html(id: &Uuid) -> String {
     PLAYER_RESPONSE_TEMPLATE.replace("$ID", &id.to_string())
 }
 
-pub fn server_html(id: &Uuid, name: &str, player_count: usize, max_players: usize) -> String {
+pub fn server_html(server: &ServerListEntry) -> String {
+    let name = if server.has_password {
+        format!("\u{1F512} {}", escape_html(&server.title))
+    } else {
+        escape_html(&server.title)
+    };
+    let status = match server.phase {
+        GamePhase::Waiting => "Waiting".to_owned(),
+        GamePhase::Playing => format!("Round {}", server.round),
+        GamePhase::Ended => "Ended".to_owned(),
+    };
+
     SERVER_TEMPLATE
-        .replace("$SERVER_ID", &id.to_string())
-        .replace("$SERVER_NAME", name)
-        .replace("$PLAYER_NUM", &player_count.to_string())
-        .replace("$MAX_PLAYERS", &max_players.to_string())
+        .replace("$SERVER_ID", &server.id.to_string())
+        .replace("$SERVER_NAME", &name)
+        .replace("$HOST_NAME", &escape_html(&server.host_name))
+        .replace("$STATUS", &status)
+        .replace("$PLAYER_NUM", &server.players.to_string())
+        .replace("$MAX_PLAYERS", &server.max_players.unwrap_or(0).to_string())
+        .replace("$SPECTATORS", &server.spectators.to_string())
+        .replace("$POINTS_TO_WIN", &server.points_to_win.to_string())
+        .replace("$PACK_NAMES", &escape_html(&server.packs.join(", ")))
+        .replace("$PACK_COUNT", &server.packs.len().to_string())
+}
+
+fn responses_html(responses: &[ResponseData]) -> String {
+    responses
+        .iter()
+        .map(|response| markup_to_html(&response.text))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+// Template variables
+// $ROUND the round number
+// $PROMPT the text of the prompt
+// $WINNERS each winner's name and responses
+pub fn round_entry_html(round: &RoundRecord, player_name: impl Fn(&Uuid) -> String) -> String {
+    let winners = round
+        .submissions
+        .iter()
+        .filter(|submission| round.winners.contains(&submission.player))
+        .map(|submission| {
+            format!(
+                "{}: {}",
+                escape_html(&player_name(&submission.player)),
+                responses_html(&submission.responses)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    ROUND_ENTRY_TEMPLATE
+        .replace("$ROUND", &round.round.to_string())
+        .replace("$PROMPT", &markup_to_html(&round.prompt.text))
+        .replace(
+            "$WINNERS",
+            &if winners.is_empty() {
+                "No winner".to_owned()
+            } else {
+                winners.join("<br>")
+            },
+        )
+}
+
+// Template variables
+// $NAME the name of the player
+// $POINTS the player's final points
+// $ROUNDS_WON the number of rounds the player won
+// $FASTEST_PICK the player's fastest pick in seconds
+pub fn standing_html(stats: &PlayerStats) -> String {
+    STANDING_TEMPLATE
+        .replace("$NAME", &escape_html(&stats.name))
+        .replace("$POINTS", &stats.points.to_string())
+        .replace("$ROUNDS_WON", &stats.rounds_won.to_string())
+        .replace(
+            "$FASTEST_PICK",
+            &stats
+                .fastest_pick_ms
+                .map(|ms| format!("{:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_else(|| "-".to_owned()),
+        )
+}
+
+// Template variables
+// $PROMPT the text of the prompt
+// $RESPONSES the responses that were played
+// $NAME the name of the player who played them
+// $COUNTS the number of votes and reactions the combo got, if any
+pub fn combo_html(combo: &Combo, name: &str) -> String {
+    let mut counts = Vec::new();
+    match combo.submission.votes {
+        0 => {}
+        1 => counts.push("1 vote".to_owned()),
+        votes => counts.push(format!("{} votes", votes)),
+    }
+    match combo.submission.reactions {
+        0 => {}
+        1 => counts.push("1 reaction".to_owned()),
+        reactions => counts.push(format!("{} reactions", reactions)),
+    }
+    let counts = if counts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", counts.join(", "))
+    };
+
+    COMBO_TEMPLATE
+        .replace("$PROMPT", &markup_to_html(&combo.prompt.text))
+        .replace("$RESPONSES", &responses_html(&combo.submission.responses))
+        .replace("$NAME", &escape_html(name))
+        .replace("$COUNTS", &counts)
+}
+
+/// A row of buttons for reacting to the submission of the given player
+pub fn reaction_bar_html(player: &Uuid) -> String {
+    let buttons =
+        Reaction::ALL
+            .iter()
+            .enumerate()
+            .fold(String::new(), |mut html, (index, reaction)| {
+                html.push_str(&format!(
+                    r#"<span class="reaction" data-reaction="{}">{}</span>"#,
+                    index,
+                    reaction.emoji()
+                ));
+                html
+            });
+
+    format!(
+        r#"<div class="reaction-bar" data-player="{}">{}</div>"#,
+        player, buttons
+    )
+}
+
+/// A winning submission of the last round, which players can keep reacting to
+pub fn winner_reaction_html(player: &Uuid, name: &str, responses: &[ResponseData]) -> String {
+    format!(
+        r#"<div id="winner-{}" class="winner-reaction"><div>{}: {}</div>{}</div>"#,
+        player,
+        escape_html(name),
+        responses_html(responses),
+        reaction_bar_html(player)
+    )
+}
+
+pub fn set_winner_reactions(entries: &[String]) {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let winners: HtmlElement = document
+        .get_element_by_id("winner-reactions")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    winners.set_inner_html(&entries.concat());
+    winners.set_hidden(entries.is_empty());
+}
+
+/// The reaction a click landed on and the player whose submission it's for, if it was on a
+/// reaction button
+pub fn clicked_reaction(event: &Event) -> Option<(Uuid, Reaction)> {
+    let button: Element = event.target()?.dyn_into().ok()?;
+    let index = button
+        .get_attribute("data-reaction")?
+        .parse::<usize>()
+        .ok()?;
+    let reaction = *Reaction::ALL.get(index)?;
+    let bar = button.closest(".reaction-bar").ok()??;
+    let player = Uuid::parse_str(&bar.get_attribute("data-player")?).ok()?;
+
+    Some((player, reaction))
+}
+
+pub fn set_round_history(entries: &[String]) {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let history = document.get_element_by_id("round-history-list").unwrap();
+    history.set_inner_html(&entries.concat());
+}
+
+pub fn add_round_entry(entry: &str) {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let history = document.get_element_by_id("round-history-list").unwrap();
+    history.set_inner_html(&format!("{}{}", history.inner_html(), entry));
+}
+
+pub fn set_game_summary(standings: &[String], combos: &[String], crowd_favorite: Option<String>) {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let favorite: HtmlElement = document
+        .get_element_by_id("crowd-favorite")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    favorite.set_hidden(crowd_favorite.is_none());
+    favorite.set_inner_html(&crowd_favorite.map_or(String::new(), |combo| {
+        format!(
+            r#"<div class="crowd-favorite-label">Crowd Favorite</div>{}"#,
+            combo
+        )
+    }));
+    document
+        .get_element_by_id("standings-body")
+        .unwrap()
+        .set_inner_html(&standings.concat());
+    document
+        .get_element_by_id("best-combos")
+        .unwrap()
+        .set_inner_html(&combos.concat());
 }
 
 pub fn init_game() {
@@ -101,12 +338,7 @@ pub fn init_lobby() {
     game.set_hidden(true);
 }
 
-pub fn add_server(
-    server_id: &Uuid,
-    name: &str,
-    num_players: usize,
-    max_players: Option<usize>,
-) -> HtmlElement {
+pub fn add_server(server: &ServerListEntry) -> HtmlElement {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let server_list = document.get_element_by_id("game-list").unwrap();
@@ -114,16 +346,11 @@ pub fn add_server(
     let base_element = document.create_element("div").unwrap();
     server_list.append_child(&base_element).unwrap();
     let server_entry = server_list.last_element_child().unwrap();
-    server_entry.set_outer_html(&server_html(
-        server_id,
-        name,
-        num_players,
-        max_players.unwrap_or(0),
-    ));
-
-    if max_players.is_some() {
+    server_entry.set_outer_html(&server_html(server));
+
+    if server.max_players.is_some() {
         let span: HtmlElement = document
-            .get_element_by_id(&format!("{}-max-players", server_id))
+            .get_element_by_id(&format!("{}-max-players", server.id))
             .unwrap()
             .dyn_into()
             .unwrap();
@@ -138,6 +365,56 @@ pub fn add_server(
         .unwrap()
 }
 
+/// Reads how the player wants the server list sorted and filtered
+pub fn get_server_view() -> (ServerSort, ServerFilter) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let search_ele: HtmlInputElement = document
+        .get_element_by_id("server-search")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let sort_ele: HtmlSelectElement = document
+        .get_element_by_id("server-sort")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let phase_ele: HtmlSelectElement = document
+        .get_element_by_id("server-phase")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let hide_full_ele: HtmlInputElement = document
+        .get_element_by_id("hide-full")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let hide_password_ele: HtmlInputElement = document
+        .get_element_by_id("hide-password")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+
+    let sort = match sort_ele.value().as_str() {
+        "title" => ServerSort::Title,
+        "round" => ServerSort::Round,
+        "spectators" => ServerSort::Spectators,
+        _ => ServerSort::Players,
+    };
+    let phase = match phase_ele.value().as_str() {
+        "waiting" => Some(GamePhase::Waiting),
+        "playing" => Some(GamePhase::Playing),
+        "ended" => Some(GamePhase::Ended),
+        _ => None,
+    };
+
+    (sort, ServerFilter {
+        search: search_ele.value(),
+        phase,
+        hide_full: hide_full_ele.checked(),
+        hide_password: hide_password_ele.checked(),
+    })
+}
+
 pub fn get_name_input() -> HtmlElement {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
@@ -154,7 +431,7 @@ pub fn get_name_input_value() -> String {
     input.value()
 }
 
-pub fn set_player_responses(id: &Uuid, cards: &Vec<ResponseData>) -> HtmlElement {
+pub fn set_player_responses(id: &Uuid, cards: &Vec<ResponseData>, reactable: bool) -> HtmlElement {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let responses = document.get_element_by_id("played-cards").unwrap();
@@ -168,10 +445,14 @@ pub fn set_player_responses(id: &Uuid, cards: &Vec<ResponseData>) -> HtmlElement
         .get_element_by_id(&format!("player-{}-responses", id))
         .unwrap();
 
-    player_res.set_inner_html(&cards.iter().fold(String::new(), |mut string, c| {
+    let mut html = cards.iter().fold(String::new(), |mut string, c| {
         string.push_str(&response_card_html(c));
         string
-    }));
+    });
+    if reactable {
+        html.push_str(&reaction_bar_html(id));
+    }
+    player_res.set_inner_html(&html);
 
     player_res.dyn_into().unwrap()
 }
@@ -258,10 +539,155 @@ pub fn get_settings() -> GameSettings {
         .unwrap()
         .dyn_into()
         .unwrap();
+    let blank_cards_ele: HtmlInputElement = document
+        .get_element_by_id("blank-cards")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let save_write_ins_ele: HtmlInputElement = document
+        .get_element_by_id("save-write-ins")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let rebooting_ele: HtmlInputElement = document
+        .get_element_by_id("rebooting-the-universe")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let packing_heat_ele: HtmlInputElement = document
+        .get_element_by_id("packing-heat")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let judging_mode_ele: HtmlSelectElement = document
+        .get_element_by_id("judging-mode")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let vote_tie_break_ele: HtmlSelectElement = document
+        .get_element_by_id("vote-tie-break")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let hand_size_ele: HtmlInputElement = document
+        .get_element_by_id("hand-size")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let end_condition_ele: HtmlSelectElement = document
+        .get_element_by_id("end-condition")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let end_limit_ele: HtmlInputElement = document
+        .get_element_by_id("end-limit")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let end_tie_break_ele: HtmlSelectElement = document
+        .get_element_by_id("end-tie-break")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let czar_rotation_ele: HtmlSelectElement = document
+        .get_element_by_id("czar-rotation")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let late_join_ele: HtmlSelectElement = document
+        .get_element_by_id("late-join")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let password_ele: HtmlInputElement = document
+        .get_element_by_id("password")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let unlisted_ele: HtmlInputElement = document
+        .get_element_by_id("unlisted")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let late_join_handicap_ele: HtmlInputElement = document
+        .get_element_by_id("late-join-handicap")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let skip_vote_threshold_ele: HtmlInputElement = document
+        .get_element_by_id("skip-vote-threshold")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let afk_threshold_ele: HtmlInputElement = document
+        .get_element_by_id("afk-threshold")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let kick_afk_ele: HtmlInputElement = document
+        .get_element_by_id("kick-afk")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let pause_timeout_ele: HtmlInputElement = document
+        .get_element_by_id("pause-timeout")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let title_ele: HtmlInputElement = document
+        .get_element_by_id("game-title")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let seed_ele: HtmlInputElement = document
+        .get_element_by_id("seed")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
 
     let max_players = max_players_ele.value().parse().ok();
     // let max_time = max_time_ele.value().parse().ok();
     let points = points_ele.value().parse().unwrap();
+    let blank_cards = blank_cards_ele.value().parse().unwrap_or(0);
+    let save_write_ins = save_write_ins_ele.checked();
+    let rebooting_the_universe = rebooting_ele.checked();
+    let packing_heat = packing_heat_ele.checked();
+    let judging_mode = match judging_mode_ele.value().as_str() {
+        "democracy" => JudgingMode::Democracy,
+        _ => JudgingMode::Czar,
+    };
+    let vote_tie_break = parse_tie_break(&vote_tie_break_ele.value());
+    let hand_size = hand_size_ele.value().parse().unwrap_or(10);
+    let end_limit = end_limit_ele.value().parse().unwrap_or(0);
+    let end_condition = match end_condition_ele.value().as_str() {
+        "rounds" => EndCondition::Rounds(end_limit),
+        "time" => EndCondition::TimeLimit(end_limit),
+        "deck" => EndCondition::DeckExhausted,
+        _ => EndCondition::Points,
+    };
+    let end_tie_break = parse_tie_break(&end_tie_break_ele.value());
+    let czar_rotation = match czar_rotation_ele.value().as_str() {
+        "winner" => CzarRotation::WinnerJudges,
+        "random" => CzarRotation::Random,
+        _ => CzarRotation::Sequential,
+    };
+    let late_join = match late_join_ele.value().as_str() {
+        "next-round" => LateJoin::NextRound,
+        "spectate" => LateJoin::Spectate,
+        "block" => LateJoin::Block,
+        _ => LateJoin::Allow,
+    };
+    let late_join_handicap = late_join_handicap_ele.checked();
+    // An empty password means the game is open to everyone
+    let password = Some(password_ele.value()).filter(|password| !password.is_empty());
+    let unlisted = unlisted_ele.checked();
+    let title = title_ele.value().trim().to_owned();
+    let afk_threshold = afk_threshold_ele.value().parse().unwrap_or(2);
+    let kick_afk = kick_afk_ele.checked();
+    let pause_timeout = pause_timeout_ele.value().parse().unwrap_or(5);
+    // Leaving the threshold empty turns skipping off
+    let skip_vote_threshold = skip_vote_threshold_ele.value().parse().ok();
+    let seed = seed_ele.value().trim().parse().ok();
     let packs = current_packs();
 
     GameSettings {
@@ -270,7 +696,138 @@ pub fn get_settings() -> GameSettings {
         // Since we don't support max_selection_time yet we don't enable it
         max_selection_time: None,
         points_to_win: points,
+        blank_cards,
+        save_write_ins,
+        rebooting_the_universe,
+        packing_heat,
+        judging_mode,
+        vote_tie_break,
+        hand_size,
+        end_condition,
+        end_tie_break,
+        czar_rotation,
+        pause_timeout,
+        skip_vote_threshold,
+        afk_threshold,
+        kick_afk,
+        late_join,
+        late_join_handicap,
+        password,
+        unlisted,
+        title,
+        seed,
+    }
+}
+
+fn parse_tie_break(value: &str) -> TieBreak {
+    match value {
+        "all-win" => TieBreak::AllWin,
+        "no-winner" => TieBreak::NoWinner,
+        _ => TieBreak::Random,
+    }
+}
+
+/// Asks the player for a game's password, returning `None` if they cancel
+pub fn prompt_password() -> Option<String> {
+    web_sys::window()
+        .unwrap()
+        .prompt_with_message("This game needs a password")
+        .ok()
+        .flatten()
+}
+
+/// Reads a `#/join/<code or id>` invite link from the address the page was opened with
+pub fn invite_from_url() -> Option<Invite> {
+    let hash = web_sys::window().unwrap().location().hash().ok()?;
+    let target = hash.strip_prefix("#/join/")?.trim_end_matches('/');
+
+    if is_invite_code(target) {
+        Some(Invite::Code(target.to_ascii_uppercase()))
+    } else {
+        Uuid::parse_str(target).ok().map(Invite::Id)
+    }
+}
+
+/// Removes the invite link from the address so reloading doesn't join the game again
+pub fn clear_invite_from_url() {
+    let _ = web_sys::window().unwrap().location().set_hash("");
+}
+
+fn chat_scope_name(scope: ChatScope) -> &'static str {
+    match scope {
+        ChatScope::Lobby => "lobby",
+        ChatScope::Game => "game",
+    }
+}
+
+pub fn show_chat_message(scope: ChatScope, message: &ChatMessage) {
+    add_chat_message(
+        chat_scope_name(scope),
+        &message.name,
+        &message.text,
+        message.time as f64,
+    );
+}
+
+/// Shows a line in the chat that only this player sees, like why their message wasn't sent
+pub fn show_chat_notice(scope: ChatScope, text: &str) {
+    add_chat_notice(chat_scope_name(scope), text);
+}
+
+pub fn clear_chat(scope: ChatScope) {
+    clear_chat_log(chat_scope_name(scope));
+}
+
+/// Takes what's been typed into a chat box, emptying it
+pub fn take_chat_input(scope: ChatScope) -> String {
+    let input: HtmlInputElement = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .get_element_by_id(&format!("{}-chat-input", chat_scope_name(scope)))
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+
+    let text = input.value();
+    input.set_value("");
+    text
+}
+
+pub fn show_error(message: &str) {
+    web_sys::window()
+        .unwrap()
+        .alert_with_message(message)
+        .unwrap();
+}
+
+/// Asks the player what to write on a blank card, returning `None` if they cancel
+pub fn prompt_write_in() -> Option<String> {
+    let window = web_sys::window().unwrap();
+    let message = format!(
+        "Write your answer (up to {} characters)",
+        MAX_WRITE_IN_LENGTH
+    );
+
+    window
+        .prompt_with_message(&message)
+        .ok()
+        .flatten()
+        .map(|text| text.trim().to_owned())
+        .filter(|text| !text.is_empty())
+}
+
+/// The game and token saved from the last game joined, if there is one
+pub fn rejoin_token() -> Option<(Uuid, Uuid)> {
+    let saved = get_rejoin_token();
+    if saved.is_null() {
+        return None;
     }
+
+    let saved = Array::from(&saved);
+    let game_id = Uuid::parse_str(&saved.get(0).as_string()?).ok()?;
+    let token = Uuid::parse_str(&saved.get(1).as_string()?).ok()?;
+    Some((game_id, token))
 }
 
 #[wasm_bindgen]
@@ -283,8 +840,15 @@ extern "C" {
     pub fn clear_player_marks(id: &str);
     pub fn mark_player_czar(id: &str);
     pub fn mark_player_played(id: &str);
+    pub fn mark_player_voted(id: &str);
+    pub fn mark_game_winner(id: &str);
+    pub fn mark_player_afk(id: &str, afk: bool);
+    pub fn show_spectating(message: &str);
+    pub fn hide_spectating();
+    pub fn show_response_votes(id: &str, votes: u32);
     pub fn update_player_name(id: &str, name: &str);
     pub fn update_player_points(id: &str, points: u32);
+    pub fn update_skip_votes(votes: u32, needed: u32);
     pub fn remove_card_from_hand(index: u8);
     pub fn remove_player(id: &str);
     pub fn clear_response_cards();
@@ -294,10 +858,21 @@ extern "C" {
     pub fn enable_hand();
     pub fn show_game_end();
     pub fn hide_game_end();
+    pub fn show_paused(resumes_in: u32);
+    pub fn hide_paused();
     pub fn mark_winner(id: &str);
     fn get_current_packs() -> JsValue;
+    fn get_rejoin_token() -> JsValue;
+    pub fn save_rejoin_token(game_id: &str, token: &str);
+    pub fn clear_rejoin_token();
+    pub fn set_invite_code(code: &str);
+    fn add_chat_message(scope: &str, name: &str, text: &str, time: f64);
+    fn add_chat_notice(scope: &str, text: &str);
+    fn clear_chat_log(scope: &str);
+    pub fn mark_player_muted(id: &str, muted: bool);
     pub fn clear_hand();
     pub fn disable_start_game();
     pub fn clear_blank_responses();
     pub fn remove_response(id: &str);
+    pub fn show_reaction(element_id: &str, emoji: &str);
 }
@@ -1,17 +1,23 @@
 use std::sync::{Arc, Mutex};
 
-use common::protocol::serverbound::ServerBoundPacket;
+use common::{
+    data::text::sanitize_name,
+    protocol::{clientbound::ChatScope, serverbound::ServerBoundPacket, BotKind, ReactionTarget},
+};
 use wasm_bindgen::{prelude::Closure, JsCast};
-use web_sys::HtmlElement;
+use web_sys::{Event, HtmlElement, KeyboardEvent};
 
 use crate::{
-    game::{GameManager, GameState},
+    game::{refresh_servers, send_chat, send_reaction, GameManager, GameState},
     html::{
+        clicked_reaction,
+        current_packs,
         get_name_input,
         get_name_input_value,
         get_settings,
         hide_game_end,
         init_lobby,
+        show_error,
         update_player_name,
     },
 };
@@ -27,8 +33,29 @@ pub fn register_events(manager: Arc<Mutex<GameManager>>) {
         let manager_arc = input_manager.clone();
         let mut manager = manager_arc.lock().unwrap();
         let new_name = get_name_input_value();
+        if new_name.trim().is_empty() {
+            update_player_name(&manager.id.to_string(), &new_name);
+            manager.player.name = new_name;
+            return;
+        }
+
+        let new_name = match sanitize_name(&new_name) {
+            Ok(name) => name,
+            Err(reason) => {
+                show_error(&reason);
+                return;
+            }
+        };
         update_player_name(&manager.id.to_string(), &new_name);
         manager.player.name = new_name.clone();
+
+        // The lobby uses our name for chat, and the game shows it to everyone else
+        manager
+            .socket
+            .lock()
+            .unwrap()
+            .send_packet(&ServerBoundPacket::SetPlayerName(new_name))
+            .unwrap();
     });
 
     input.set_onchange(Some(input_change.as_ref().unchecked_ref()));
@@ -65,6 +92,34 @@ pub fn register_events(manager: Arc<Mutex<GameManager>>) {
     create_game_button.set_onclick(Some(create_game_click.as_ref().unchecked_ref()));
     create_game_click.forget();
 
+    let quick_match_button: HtmlElement = document
+        .get_element_by_id("quick-match-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let quick_match_start_button = start_game_button.clone();
+    let quick_match_manager = manager.clone();
+
+    let quick_match_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = quick_match_manager.clone();
+        let mut manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        // Prefer games using the packs picked in the new game menu
+        let id = socket
+            .send_packet_with_id(ServerBoundPacket::QuickMatch {
+                packs: current_packs(),
+            })
+            .unwrap();
+        drop(socket);
+        manager.packet_cache.insert(
+            id,
+            crate::game::CachedPacket::QuickMatch(quick_match_start_button.clone()),
+        );
+    });
+
+    quick_match_button.set_onclick(Some(quick_match_click.as_ref().unchecked_ref()));
+    quick_match_click.forget();
+
     let start_game_button_clone = start_game_button.clone();
     let start_game_manager = manager.clone();
 
@@ -126,14 +181,237 @@ pub fn register_events(manager: Arc<Mutex<GameManager>>) {
     let refresh_manager = manager.clone();
 
     let refresh_click = Closure::<dyn FnMut()>::new(move || {
-        let manager_arc = refresh_manager.clone();
+        refresh_servers(refresh_manager.clone());
+    });
+
+    refresh_button.set_onclick(Some(refresh_click.as_ref().unchecked_ref()));
+    refresh_click.forget();
+
+    // Changing how the list is sorted or filtered asks for it again
+    let view_manager = manager.clone();
+    let view_change = Closure::<dyn FnMut()>::new(move || {
+        refresh_servers(view_manager.clone());
+    });
+
+    for id in ["server-sort", "server-phase", "hide-full", "hide-password"] {
+        let element: HtmlElement = document.get_element_by_id(id).unwrap().dyn_into().unwrap();
+        element.set_onchange(Some(view_change.as_ref().unchecked_ref()));
+    }
+    let search: HtmlElement = document
+        .get_element_by_id("server-search")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    search.set_oninput(Some(view_change.as_ref().unchecked_ref()));
+    view_change.forget();
+
+    for scope in [ChatScope::Lobby, ChatScope::Game] {
+        let input_id = match scope {
+            ChatScope::Lobby => "lobby-chat-input",
+            ChatScope::Game => "game-chat-input",
+        };
+        let chat_input: HtmlElement = document
+            .get_element_by_id(input_id)
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        let chat_manager = manager.clone();
+
+        let chat_keydown = Closure::<dyn FnMut(KeyboardEvent)>::new(move |event: KeyboardEvent| {
+            if event.key() == "Enter" {
+                send_chat(chat_manager.clone(), scope);
+            }
+        });
+
+        chat_input.set_onkeydown(Some(chat_keydown.as_ref().unchecked_ref()));
+        chat_keydown.forget();
+    }
+
+    // Reaction buttons are added and removed with each round, so clicks are caught by the
+    // elements holding them
+    let played_cards: HtmlElement = document
+        .get_element_by_id("played-cards")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let played_manager = manager.clone();
+
+    let played_click = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+        if let Some((player, reaction)) = clicked_reaction(&event) {
+            // Reacting shouldn't also pick the submission as the winner
+            event.stop_propagation();
+            send_reaction(
+                played_manager.clone(),
+                ReactionTarget::Submission(player),
+                reaction,
+            );
+        }
+    });
+
+    // Listen while capturing so this runs before the submission's own click handler
+    played_cards
+        .add_event_listener_with_callback_and_bool(
+            "click",
+            played_click.as_ref().unchecked_ref(),
+            true,
+        )
+        .unwrap();
+    played_click.forget();
+
+    let winner_reactions: HtmlElement = document
+        .get_element_by_id("winner-reactions")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let winner_manager = manager.clone();
+
+    let winner_click = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+        if let Some((player, reaction)) = clicked_reaction(&event) {
+            send_reaction(
+                winner_manager.clone(),
+                ReactionTarget::Winner(player),
+                reaction,
+            );
+        }
+    });
+
+    winner_reactions.set_onclick(Some(winner_click.as_ref().unchecked_ref()));
+    winner_click.forget();
+
+    let add_bot_button: HtmlElement = document
+        .get_element_by_id("add-bot-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let add_bot_manager = manager.clone();
+
+    let add_bot_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = add_bot_manager.clone();
         let manager = manager_arc.lock().unwrap();
         let socket = manager.socket.lock().unwrap();
         socket
-            .send_packet(&ServerBoundPacket::RefreshServerList)
+            .send_packet(&ServerBoundPacket::AddBot(BotKind::Rando))
             .unwrap();
     });
 
-    refresh_button.set_onclick(Some(refresh_click.as_ref().unchecked_ref()));
-    refresh_click.forget();
+    add_bot_button.set_onclick(Some(add_bot_click.as_ref().unchecked_ref()));
+    add_bot_click.forget();
+
+    let remove_bot_button: HtmlElement = document
+        .get_element_by_id("remove-bot-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let remove_bot_manager = manager.clone();
+
+    let remove_bot_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = remove_bot_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let bot_id = match manager.others.iter().find(|(_, player)| player.is_bot) {
+            Some((id, _)) => *id,
+            None => return,
+        };
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::RemoveBot(bot_id))
+            .unwrap();
+    });
+
+    remove_bot_button.set_onclick(Some(remove_bot_click.as_ref().unchecked_ref()));
+    remove_bot_click.forget();
+
+    let reboot_button: HtmlElement = document
+        .get_element_by_id("reboot-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let reboot_manager = manager.clone();
+
+    let reboot_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = reboot_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::RebootTheUniverse)
+            .unwrap();
+    });
+
+    reboot_button.set_onclick(Some(reboot_click.as_ref().unchecked_ref()));
+    reboot_click.forget();
+
+    let pause_button: HtmlElement = document
+        .get_element_by_id("pause-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let pause_manager = manager.clone();
+
+    let pause_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = pause_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::SetPaused(true))
+            .unwrap();
+    });
+
+    pause_button.set_onclick(Some(pause_click.as_ref().unchecked_ref()));
+    pause_click.forget();
+
+    let resume_button: HtmlElement = document
+        .get_element_by_id("resume-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let resume_manager = manager.clone();
+
+    let resume_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = resume_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::SetPaused(false))
+            .unwrap();
+    });
+
+    resume_button.set_onclick(Some(resume_click.as_ref().unchecked_ref()));
+    resume_click.forget();
+
+    let redraw_button: HtmlElement = document
+        .get_element_by_id("redraw-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let redraw_manager = manager.clone();
+
+    let redraw_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = redraw_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::RedrawPrompt)
+            .unwrap();
+    });
+
+    redraw_button.set_onclick(Some(redraw_click.as_ref().unchecked_ref()));
+    redraw_click.forget();
+
+    let skip_button: HtmlElement = document
+        .get_element_by_id("skip-button")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    let skip_manager = manager.clone();
+
+    let skip_click = Closure::<dyn FnMut()>::new(move || {
+        let manager_arc = skip_manager.clone();
+        let manager = manager_arc.lock().unwrap();
+        let socket = manager.socket.lock().unwrap();
+        socket
+            .send_packet(&ServerBoundPacket::VoteToSkipPrompt)
+            .unwrap();
+    });
+
+    skip_button.set_onclick(Some(skip_click.as_ref().unchecked_ref()));
+    skip_click.forget();
 }
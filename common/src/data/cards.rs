@@ -7,6 +7,9 @@ use serde::{
     Serializer,
 };
 
+/// The maximum length of the text a player can write on a blank response card
+pub const MAX_WRITE_IN_LENGTH: usize = 100;
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct CardID {
     pub pack_number: usize,
@@ -14,12 +17,23 @@ pub struct CardID {
 }
 
 impl CardID {
+    // Blank cards don't belong to a loaded pack so they use a pack number no game can reach
+    const BLANK_PACK: usize = usize::MAX;
+
     pub fn new(pack_number: usize, card_number: usize) -> Self {
         CardID {
             pack_number,
             card_number,
         }
     }
+
+    pub fn blank(card_number: usize) -> Self {
+        CardID::new(Self::BLANK_PACK, card_number)
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.pack_number == Self::BLANK_PACK
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -48,6 +62,9 @@ impl Eq for Pack {}
 pub struct Prompt {
     pub text: String,
     pub pick: u8,
+    // Some prompts have players draw extra cards before picking their responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draw: Option<u8>,
 }
 
 pub type Response = String;
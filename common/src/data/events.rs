@@ -0,0 +1,176 @@
+use crate::{
+    data::cards::Prompt,
+    protocol::{clientbound::ResponseData, GameSetting, GameSettings},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Something that happened in a game, recorded so the game can be replayed later
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum GameEvent {
+    // Always the first event. The seed in the settings is the one the game actually used.
+    GameCreated(GameSettings),
+    PlayerJoined {
+        id: Uuid,
+        name: String,
+        is_bot: bool,
+    },
+    PlayerLeft(Uuid),
+    // A player rejoined a restored game under a new connection
+    PlayerReconnected {
+        old_id: Uuid,
+        new_id: Uuid,
+    },
+    PlayerRenamed {
+        id: Uuid,
+        name: String,
+    },
+    SettingChanged(GameSetting),
+    GameStarted,
+    RoundStarted {
+        round: u32,
+        czar: Option<Uuid>,
+        prompt: Prompt,
+    },
+    ResponsesSubmitted {
+        player: Uuid,
+        responses: Vec<ResponseData>,
+    },
+    RoundWon(Vec<Uuid>),
+    RoundCancelled,
+    // The prompt was redrawn or skipped, and every submission went back to its player's hand
+    PromptReplaced(Prompt),
+    GamePaused,
+    GameResumed,
+    // Points changing outside of winning a round, such as when rebooting the universe
+    PointsChanged {
+        id: Uuid,
+        points: u32,
+    },
+    GameEnded(Vec<Uuid>),
+}
+
+/// A [GameEvent] along with when it happened
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoggedEvent {
+    // Milliseconds since the unix epoch
+    pub time: i64,
+    pub event: GameEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayPlayer {
+    pub id: Uuid,
+    pub name: String,
+    pub is_bot: bool,
+    pub points: u32,
+}
+
+/// The state of a game at some point in its event log
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReplayState {
+    pub settings: Option<GameSettings>,
+    pub players: Vec<ReplayPlayer>,
+    pub round: u32,
+    pub czar: Option<Uuid>,
+    pub prompt: Option<Prompt>,
+    pub submissions: Vec<(Uuid, Vec<ResponseData>)>,
+    pub round_winners: Vec<Uuid>,
+    pub paused: bool,
+    // Empty until the game ends
+    pub game_winners: Vec<Uuid>,
+}
+
+impl ReplayState {
+    /// Rebuilds the state of a game after the first `count` events of its log
+    pub fn replay(events: &[LoggedEvent], count: usize) -> Self {
+        let mut state = ReplayState::default();
+        for logged in events.iter().take(count) {
+            state.apply(&logged.event);
+        }
+        state
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::GameCreated(settings) => self.settings = Some(settings.clone()),
+            GameEvent::PlayerJoined { id, name, is_bot } => self.players.push(ReplayPlayer {
+                id: *id,
+                name: name.clone(),
+                is_bot: *is_bot,
+                points: 0,
+            }),
+            GameEvent::PlayerLeft(id) => self.players.retain(|player| &player.id != id),
+            GameEvent::PlayerReconnected { old_id, new_id } => {
+                let replace = |id: &mut Uuid| {
+                    if id == old_id {
+                        *id = *new_id;
+                    }
+                };
+
+                for player in self.players.iter_mut() {
+                    replace(&mut player.id);
+                }
+                for (player, _) in self.submissions.iter_mut() {
+                    replace(player);
+                }
+                if let Some(czar) = self.czar.as_mut() {
+                    replace(czar);
+                }
+                self.round_winners.iter_mut().for_each(replace);
+            }
+            GameEvent::PlayerRenamed { id, name } =>
+                if let Some(player) = self.player_mut(id) {
+                    player.name = name.clone();
+                },
+            GameEvent::SettingChanged(setting) =>
+                if let Some(settings) = self.settings.as_mut() {
+                    settings.apply(setting);
+                },
+            GameEvent::GameStarted => {
+                for player in self.players.iter_mut() {
+                    player.points = 0;
+                }
+                self.round = 0;
+                self.game_winners.clear();
+            }
+            GameEvent::RoundStarted {
+                round,
+                czar,
+                prompt,
+            } => {
+                self.round = *round;
+                self.czar = *czar;
+                self.prompt = Some(prompt.clone());
+                self.submissions.clear();
+                self.round_winners.clear();
+            }
+            GameEvent::ResponsesSubmitted { player, responses } =>
+                self.submissions.push((*player, responses.clone())),
+            GameEvent::RoundWon(winners) => {
+                for winner in winners {
+                    if let Some(player) = self.player_mut(winner) {
+                        player.points += 1;
+                    }
+                }
+                self.round_winners = winners.clone();
+            }
+            GameEvent::RoundCancelled => self.submissions.clear(),
+            GameEvent::PromptReplaced(prompt) => {
+                self.prompt = Some(prompt.clone());
+                self.submissions.clear();
+            }
+            GameEvent::GamePaused => self.paused = true,
+            GameEvent::GameResumed => self.paused = false,
+            GameEvent::PointsChanged { id, points } =>
+                if let Some(player) = self.player_mut(id) {
+                    player.points = *points;
+                },
+            GameEvent::GameEnded(winners) => self.game_winners = winners.clone(),
+        }
+    }
+
+    fn player_mut(&mut self, id: &Uuid) -> Option<&mut ReplayPlayer> {
+        self.players.iter_mut().find(|player| &player.id == id)
+    }
+}
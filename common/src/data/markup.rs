@@ -0,0 +1,134 @@
+/// A run of text and how it's styled, after parsing the markup card text can use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Span {
+    Text(String),
+    Italic(String),
+    LineBreak,
+}
+
+/// Splits text into spans. Newlines break the line and `*asterisks*` around some text make it
+/// italic. Any asterisk without a partner later on the same line is kept as it is.
+pub fn parse_markup(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            spans.push(Span::LineBreak);
+        }
+
+        let mut rest = line.trim_end_matches('\r');
+        let mut plain = String::new();
+        while let Some(start) = rest.find('*') {
+            let after = &rest[start + 1 ..];
+            match after.find('*') {
+                Some(end) if end > 0 => {
+                    plain.push_str(&rest[.. start]);
+                    if !plain.is_empty() {
+                        spans.push(Span::Text(std::mem::take(&mut plain)));
+                    }
+                    spans.push(Span::Italic(after[.. end].to_owned()));
+                    rest = &after[end + 1 ..];
+                }
+                _ => {
+                    plain.push_str(&rest[..= start]);
+                    rest = after;
+                }
+            }
+        }
+        plain.push_str(rest);
+
+        if !plain.is_empty() {
+            spans.push(Span::Text(plain));
+        }
+    }
+
+    spans
+}
+
+/// Escapes every character with a special meaning in HTML, so the text is shown exactly as it
+/// was written whether it ends up in an element or an attribute. Dollar signs are escaped too so
+/// text filled into a template can't be mistaken for one of its `$VARIABLES`.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '$' => escaped.push_str("&#36;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders text as HTML using only the tags for its markup, escaping everything else
+pub fn markup_to_html(text: &str) -> String {
+    parse_markup(text)
+        .into_iter()
+        .map(|span| match span {
+            Span::Text(text) => escape_html(&text),
+            Span::Italic(text) => format!("<i>{}</i>", escape_html(&text)),
+            Span::LineBreak => "<br>".to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_passes_through() {
+        assert_eq!(markup_to_html("Just some words."), "Just some words.");
+        assert_eq!(parse_markup(""), Vec::new());
+    }
+
+    #[test]
+    fn tags_are_escaped() {
+        assert_eq!(
+            markup_to_html("<script>alert(1)</script>"),
+            "&lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(
+            markup_to_html("*<img src=x onerror=alert(1)>*"),
+            "<i>&lt;img src=x onerror=alert(1)&gt;</i>"
+        );
+    }
+
+    #[test]
+    fn attribute_breakouts_are_escaped() {
+        assert_eq!(
+            escape_html(r#"" onclick="alert(1)"#),
+            "&quot; onclick=&quot;alert(1)"
+        );
+        assert_eq!(escape_html("' onmouseover='x"), "&#39; onmouseover=&#39;x");
+        assert_eq!(escape_html("&amp; &lt;"), "&amp;amp; &amp;lt;");
+        assert_eq!(escape_html("$NAME"), "&#36;NAME");
+    }
+
+    #[test]
+    fn italics_and_line_breaks() {
+        assert_eq!(
+            markup_to_html("A *very* good\nidea"),
+            "A <i>very</i> good<br>idea"
+        );
+    }
+
+    #[test]
+    fn unbalanced_asterisks_are_kept() {
+        assert_eq!(markup_to_html("*half"), "*half");
+        assert_eq!(markup_to_html("5 * 3 = 15"), "5 * 3 = 15");
+        assert_eq!(markup_to_html("**"), "**");
+        // Italics don't continue onto the next line
+        assert_eq!(markup_to_html("*one\ntwo*"), "*one<br>two*");
+    }
+
+    #[test]
+    fn nested_asterisks_never_nest_tags() {
+        assert_eq!(markup_to_html("*a *b* c*"), "<i>a </i>b<i> c</i>");
+        assert_eq!(markup_to_html("***"), "***");
+    }
+}
@@ -1,4 +1,7 @@
 pub mod cards;
+pub mod events;
+pub mod markup;
+pub mod text;
 mod vec_map;
 
 pub use vec_map::VecMap;
@@ -0,0 +1,62 @@
+use crate::protocol::MAX_NAME_LENGTH;
+
+/// Strips control characters from `text`, trims it and collapses runs of whitespace into a
+/// single space. Fails if the result is empty or longer than `max_len` characters.
+pub fn sanitize(text: &str, max_len: usize) -> Result<String, String> {
+    let filtered = text
+        .chars()
+        .filter(|c| !c.is_control() || c.is_whitespace())
+        .collect::<String>();
+    let sanitized = filtered.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if sanitized.is_empty() {
+        Err("Text cannot be empty".to_owned())
+    } else if sanitized.chars().count() > max_len {
+        Err(format!("Text cannot be longer than {} characters", max_len))
+    } else {
+        Ok(sanitized)
+    }
+}
+
+/// Cleans up a player's name like [sanitize], failing with a reason meant for players
+pub fn sanitize_name(name: &str) -> Result<String, String> {
+    sanitize(name, MAX_NAME_LENGTH).map_err(|_| {
+        format!(
+            "Names have to be between 1 and {} characters",
+            MAX_NAME_LENGTH
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_whitespace_and_strips_control_characters() {
+        assert_eq!(
+            sanitize("  Sam\u{7}  the\t\n Great ", 100),
+            Ok("Sam the Great".to_owned())
+        );
+    }
+
+    #[test]
+    fn rejects_empty_text() {
+        assert!(sanitize("", 10).is_err());
+        assert!(sanitize(" \t\u{0}\u{1b} ", 10).is_err());
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(sanitize("éééé", 4), Ok("éééé".to_owned()));
+        assert!(sanitize("ééééé", 4).is_err());
+    }
+
+    #[test]
+    fn names_are_limited_in_length() {
+        let longest = "a".repeat(MAX_NAME_LENGTH);
+        assert_eq!(sanitize_name(&longest), Ok(longest.clone()));
+        assert!(sanitize_name(&format!("{}a", longest)).is_err());
+        assert!(sanitize_name("   ").is_err());
+    }
+}
@@ -103,3 +103,12 @@ impl<K, V, I: SliceIndex<[(K, V)]>> IndexMut<I> for VecMap<K, V> {
         IndexMut::index_mut(&mut self.inner, index)
     }
 }
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
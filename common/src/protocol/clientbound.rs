@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::GameSetting;
+use super::{GameSetting, Reaction, ReactionTarget, ServerListEntry};
 use crate::data::cards::{CardID, Pack, Prompt, Response};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -8,12 +8,20 @@ use uuid::Uuid;
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ClientBoundPacket {
     SetId(Uuid),
+    // The code other players can use to join the game
+    InviteCode(String),
+    // Lets a player reclaim their seat if the server restarts
+    RejoinToken {
+        game_id: Uuid,
+        token: Uuid,
+    },
     StartGame,
     SettingUpdate(GameSetting),
     AddPlayer {
         id: Uuid,
         name: String,
         is_host: bool,
+        is_bot: bool,
         points: u32,
     },
     UpdatePlayerName {
@@ -24,26 +32,87 @@ pub enum ClientBoundPacket {
         id: Uuid,
         new_host: Option<Uuid>,
     },
+    UpdatePoints {
+        id: Uuid,
+        points: u32,
+    },
     PlayerFinishedPicking(Uuid),
+    PlayerVoted(Uuid),
     DisplayResponses(HashMap<Uuid, Vec<ResponseData>>),
     NextRound {
-        czar: Uuid,
+        czar: Option<Uuid>,
         prompt: Prompt,
         new_responses: Vec<ResponseData>,
     },
+    NewHand(Vec<ResponseData>),
     CancelRound,
+    SkipVotes {
+        votes: usize,
+        needed: usize,
+    },
+    // Rounds don't wait on players who are AFK, and they aren't picked as czar
+    PlayerAfk {
+        id: Uuid,
+        afk: bool,
+    },
+    // The player was removed from the game and sent back to the lobby
+    Kicked(String),
+    // Sent to clients who joined a game in progress as spectators, and whether they'll be dealt
+    // in when the next round starts
+    Spectating {
+        joining_next_round: bool,
+    },
     DisplayWinner {
         winner: Uuid,
         end_game: bool,
     },
+    VoteResults {
+        votes: HashMap<Uuid, u32>,
+        winners: Vec<Uuid>,
+        end_game: bool,
+    },
+    GameWinners(Vec<Uuid>),
+    GameSummary(GameSummary),
+    // Replaces the client's round history, sent when it's requested
+    RoundHistory(Vec<RoundRecord>),
+    // A round that just finished, to add to the client's round history
+    RoundRecorded(RoundRecord),
+    // Sent whenever the game is paused or resumed, along with how many seconds are left until a
+    // paused game resumes on its own
+    PauseState {
+        paused: bool,
+        resumes_in: u64,
+    },
     Ack {
         packet_id: Uuid,
         response: PacketResponse,
     },
     ServerList {
-        servers: Vec<(Uuid, String, usize, Option<usize>)>,
+        servers: Vec<ServerListEntry>,
     },
+    // Sent to clients in the lobby as games come and go so their list stays current
+    ServerAdded(ServerListEntry),
+    ServerUpdated(ServerListEntry),
+    ServerRemoved(Uuid),
     CardPacks(Vec<(String, usize, usize)>),
+    Chat {
+        scope: ChatScope,
+        message: ChatMessage,
+    },
+    // The most recent messages, sent to clients as they join
+    ChatHistory {
+        scope: ChatScope,
+        messages: Vec<ChatMessage>,
+    },
+    // The host muted or unmuted a player
+    ChatMuted {
+        id: Uuid,
+        muted: bool,
+    },
+    Reacted {
+        target: ReactionTarget,
+        reaction: Reaction,
+    },
 }
 
 impl ClientBoundPacket {
@@ -64,6 +133,68 @@ impl ResponseData {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Submission {
+    pub player: Uuid,
+    pub responses: Vec<ResponseData>,
+    // How long the player took to pick their responses, or None for bots
+    pub pick_time_ms: Option<u64>,
+    pub votes: u32,
+    pub reactions: u32,
+}
+
+/// Where a chat message was sent
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatScope {
+    Lobby,
+    Game,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatMessage {
+    pub sender: Uuid,
+    pub name: String,
+    pub text: String,
+    // Milliseconds since the unix epoch
+    pub time: i64,
+}
+
+/// Everything that was played in a finished round
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoundRecord {
+    pub round: u32,
+    pub czar: Option<Uuid>,
+    pub prompt: Prompt,
+    pub submissions: Vec<Submission>,
+    pub winners: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerStats {
+    pub id: Uuid,
+    pub name: String,
+    pub points: u32,
+    pub rounds_won: u32,
+    pub fastest_pick_ms: Option<u64>,
+}
+
+/// A winning submission along with the prompt it answered
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Combo {
+    pub prompt: Prompt,
+    pub submission: Submission,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameSummary {
+    // Sorted from the most points to the least
+    pub standings: Vec<PlayerStats>,
+    // Every winning combo, from the most votes and reactions to the least
+    pub best_combos: Vec<Combo>,
+    // The combo with the most reactions, if anything was reacted to
+    pub crowd_favorite: Option<Combo>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum PacketResponse {
     Accepted,
@@ -2,6 +2,27 @@ pub mod clientbound;
 pub mod serverbound;
 
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+/// How many letters are in a game's invite code
+pub const INVITE_CODE_LENGTH: usize = 5;
+/// The letters invite codes are made of, leaving out ones that are easy to mix up like I, L and O
+pub const INVITE_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ";
+/// The longest chat message that can be sent
+pub const MAX_CHAT_LENGTH: usize = 200;
+/// The longest a player's name can be, so it fits in the player list
+pub const MAX_NAME_LENGTH: usize = 24;
+/// The reason given when joining a game without its password, so clients know to ask for one
+pub const PASSWORD_REQUIRED: &str = "This game needs a password";
+
+/// Whether a string could be an invite code, ignoring case
+pub fn is_invite_code(code: &str) -> bool {
+    code.len() == INVITE_CODE_LENGTH
+        && code
+            .bytes()
+            .all(|letter| INVITE_CODE_ALPHABET.contains(&letter.to_ascii_uppercase()))
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum GameSetting {
@@ -10,6 +31,83 @@ pub enum GameSetting {
     PointsToWin(u32),
     AddPack(String),
     RemovePack(String),
+    BlankCards(usize),
+    SaveWriteIns(bool),
+    RebootingTheUniverse(bool),
+    PackingHeat(bool),
+    JudgingMode(JudgingMode),
+    VoteTieBreak(TieBreak),
+    HandSize(usize),
+    EndCondition(EndCondition),
+    EndTieBreak(TieBreak),
+    CzarRotation(CzarRotation),
+    PauseTimeout(u32),
+    SkipVoteThreshold(Option<u32>),
+    AfkThreshold(u32),
+    KickAfk(bool),
+    LateJoin(LateJoin),
+    LateJoinHandicap(bool),
+    Password(Option<String>),
+    Unlisted(bool),
+    Title(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotKind {
+    Rando,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JudgingMode {
+    // A single czar picks the winner of each round
+    Czar,
+    // Every player votes for their favorite response, also known as "God is Dead"
+    Democracy,
+}
+
+/// How to pick between players who are tied
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    Random,
+    AllWin,
+    NoWinner,
+}
+
+/// How the czar is picked for each round
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CzarRotation {
+    // Players take turns in the order they joined
+    Sequential,
+    // The winner of a round judges the next one
+    WinnerJudges,
+    // A random player who hasn't judged yet, until everyone has had a turn
+    Random,
+}
+
+/// What happens to players who join a game that has already started
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LateJoin {
+    // Join straight into the current round
+    Allow,
+    // Watch until the next round starts, then join
+    NextRound,
+    // Only watch the game
+    Spectate,
+    // Can't join until the game is over
+    Block,
+}
+
+/// What ends a game
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndCondition {
+    // The first player to reach the points to win ends the game
+    Points,
+    // The game ends after a fixed number of rounds
+    Rounds(u32),
+    // The game ends with the first round finished after this many minutes
+    TimeLimit(u32),
+    // The game ends once every prompt has been played
+    DeckExhausted,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -18,6 +116,198 @@ pub struct GameSettings {
     pub max_selection_time: Option<u32>,
     pub points_to_win: u32,
     pub packs: Vec<String>,
+    pub blank_cards: usize,
+    pub save_write_ins: bool,
+    pub rebooting_the_universe: bool,
+    pub packing_heat: bool,
+    pub judging_mode: JudgingMode,
+    pub vote_tie_break: TieBreak,
+    pub hand_size: usize,
+    pub end_condition: EndCondition,
+    pub end_tie_break: TieBreak,
+    pub czar_rotation: CzarRotation,
+    // Minutes a paused game waits before resuming on its own
+    pub pause_timeout: u32,
+    // The percentage of players who need to vote to skip a prompt, or `None` to disable skipping
+    pub skip_vote_threshold: Option<u32>,
+    // How many deadlines in a row a player can miss before they're marked AFK
+    pub afk_threshold: u32,
+    // Whether AFK players are sent back to the lobby instead of being skipped
+    pub kick_afk: bool,
+    pub late_join: LateJoin,
+    // Late joiners start with as many points as whoever is in last place
+    pub late_join_handicap: bool,
+    // Needed to join the game, if set
+    pub password: Option<String>,
+    // Unlisted games are left out of the server list and can only be joined through an invite
+    pub unlisted: bool,
+    // The name shown in the server list, or empty to name the game after its host
+    pub title: String,
+    // The seed for the game's random number generator, picked at random if not given
+    pub seed: Option<u64>,
+}
+
+impl GameSettings {
+    /// Updates the settings with a single changed setting
+    pub fn apply(&mut self, setting: &GameSetting) {
+        match setting {
+            GameSetting::AddPack(pack) => self.packs.push(pack.clone()),
+            GameSetting::RemovePack(pack) => self.packs.retain(|i| i != pack),
+            &GameSetting::MaxPlayers(max_players) => self.max_players = max_players,
+            &GameSetting::MaxSelectionTime(time) => self.max_selection_time = time,
+            &GameSetting::PointsToWin(points) => self.points_to_win = points,
+            &GameSetting::BlankCards(count) => self.blank_cards = count,
+            &GameSetting::SaveWriteIns(save) => self.save_write_ins = save,
+            &GameSetting::RebootingTheUniverse(enabled) => self.rebooting_the_universe = enabled,
+            &GameSetting::PackingHeat(enabled) => self.packing_heat = enabled,
+            &GameSetting::JudgingMode(mode) => self.judging_mode = mode,
+            &GameSetting::VoteTieBreak(tie_break) => self.vote_tie_break = tie_break,
+            &GameSetting::HandSize(size) => self.hand_size = size,
+            &GameSetting::EndCondition(condition) => self.end_condition = condition,
+            &GameSetting::EndTieBreak(tie_break) => self.end_tie_break = tie_break,
+            &GameSetting::CzarRotation(rotation) => self.czar_rotation = rotation,
+            &GameSetting::PauseTimeout(minutes) => self.pause_timeout = minutes,
+            &GameSetting::SkipVoteThreshold(threshold) => self.skip_vote_threshold = threshold,
+            &GameSetting::AfkThreshold(missed) => self.afk_threshold = missed,
+            &GameSetting::KickAfk(kick) => self.kick_afk = kick,
+            &GameSetting::LateJoin(late_join) => self.late_join = late_join,
+            &GameSetting::LateJoinHandicap(enabled) => self.late_join_handicap = enabled,
+            GameSetting::Password(password) => self.password = password.clone(),
+            &GameSetting::Unlisted(unlisted) => self.unlisted = unlisted,
+            GameSetting::Title(title) => self.title = title.clone(),
+        }
+    }
+}
+
+/// The emoji players can react to submissions with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction {
+    Laugh,
+    Love,
+    Shock,
+    Cringe,
+    Fire,
+    Skull,
+}
+
+impl Reaction {
+    pub const ALL: [Reaction; 6] = [
+        Reaction::Laugh,
+        Reaction::Love,
+        Reaction::Shock,
+        Reaction::Cringe,
+        Reaction::Fire,
+        Reaction::Skull,
+    ];
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Reaction::Laugh => "\u{1F602}",
+            Reaction::Love => "\u{2764}\u{FE0F}",
+            Reaction::Shock => "\u{1F631}",
+            Reaction::Cringe => "\u{1F62C}",
+            Reaction::Fire => "\u{1F525}",
+            Reaction::Skull => "\u{1F480}",
+        }
+    }
+}
+
+/// What a reaction is aimed at, identified by the player who played it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionTarget {
+    // A submission being judged in the current round
+    Submission(Uuid),
+    // A winning submission of the round that just finished
+    Winner(Uuid),
+}
+
+/// How far along a game is
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePhase {
+    Waiting,
+    Playing,
+    Ended,
+}
+
+/// What the server list shows about a game
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerListEntry {
+    pub id: Uuid,
+    pub title: String,
+    pub host_name: String,
+    pub players: usize,
+    pub max_players: Option<usize>,
+    pub spectators: usize,
+    pub phase: GamePhase,
+    // Zero until the game starts
+    pub round: u32,
+    pub points_to_win: u32,
+    pub packs: Vec<String>,
+    pub has_password: bool,
+}
+
+impl ServerListEntry {
+    pub fn is_full(&self) -> bool {
+        self.max_players
+            .is_some_and(|max_players| self.players >= max_players)
+    }
+}
+
+/// The order games are listed in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ServerSort {
+    // The most players first
+    #[default]
+    Players,
+    Title,
+    // The furthest along first
+    Round,
+    // The most spectators first
+    Spectators,
+}
+
+impl ServerSort {
+    pub fn compare(&self, a: &ServerListEntry, b: &ServerListEntry) -> Ordering {
+        let ordering = match self {
+            ServerSort::Players => b.players.cmp(&a.players),
+            ServerSort::Title => Ordering::Equal,
+            ServerSort::Round => b.round.cmp(&a.round),
+            ServerSort::Spectators => b.spectators.cmp(&a.spectators),
+        };
+
+        // Fall back to the title so the order is stable
+        ordering.then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    }
+}
+
+/// Which games to leave out of the server list
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ServerFilter {
+    // Matched against the title, host name and packs, ignoring case
+    pub search: String,
+    pub phase: Option<GamePhase>,
+    pub hide_full: bool,
+    pub hide_password: bool,
+}
+
+impl ServerFilter {
+    pub fn matches(&self, entry: &ServerListEntry) -> bool {
+        if self.phase.is_some_and(|phase| phase != entry.phase)
+            || (self.hide_full && entry.is_full())
+            || (self.hide_password && entry.has_password)
+        {
+            return false;
+        }
+
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || entry.title.to_lowercase().contains(&search)
+            || entry.host_name.to_lowercase().contains(&search)
+            || entry
+                .packs
+                .iter()
+                .any(|pack| pack.to_lowercase().contains(&search))
+    }
 }
 
 pub fn encode<P: Serialize>(packet: &P) -> String {
@@ -1,6 +1,14 @@
 use crate::data::cards::CardID;
 
-use super::{GameSetting, GameSettings};
+use super::{
+    BotKind,
+    GameSetting,
+    GameSettings,
+    Reaction,
+    ReactionTarget,
+    ServerFilter,
+    ServerSort,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -37,13 +45,45 @@ pub enum ServerBoundPacket {
     StartGame,
     UpdateSetting(GameSetting),
     SelectResponse(CardID),
+    SelectWriteIn { card: CardID, text: String },
     SelectRoundWinner(Uuid),
+    VoteForResponse(Uuid),
+    RebootTheUniverse,
+    // The czar can swap the prompt for a new one once per round, before anyone has picked
+    RedrawPrompt,
+    VoteToSkipPrompt,
+    AddBot(BotKind),
+    RemoveBot(Uuid),
+    RequestHistory,
+    // Only the host can pause or resume the game
+    SetPaused(bool),
     LeaveGame,
+    // Only the host can mute players in a game
+    MuteChat { id: Uuid, muted: bool },
+    // Players and spectators can react to anyone's submission but their own
+    React { target: ReactionTarget, reaction: Reaction },
+
+    // Packets for both the lobby and games
+    SendChat(String),
 
     // Lobby packets
     CreateServer(GameSettings),
-    JoinGame(Uuid),
-    RefreshServerList,
+    JoinGame {
+        id: Uuid,
+        password: Option<String>,
+    },
+    JoinByCode {
+        code: String,
+        password: Option<String>,
+    },
+    Rejoin { game_id: Uuid, token: Uuid },
+    // Joins the open game that suits the player best, preferring ones using these packs, or
+    // makes a new one if none fit
+    QuickMatch { packs: Vec<String> },
+    RefreshServerList {
+        sort: ServerSort,
+        filter: ServerFilter,
+    },
     RequestCardPacks,
 }
 
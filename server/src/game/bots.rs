@@ -0,0 +1,68 @@
+use common::{
+    data::cards::{CardID, Prompt},
+    protocol::BotKind,
+};
+use rand::{seq::SliceRandom, RngCore};
+use uuid::Uuid;
+
+/// A strategy for a player controlled by the server
+pub trait Bot {
+    /// The name the bot shows up with in the player list
+    fn name(&self) -> String;
+
+    /// The kind of bot this is, used to recreate it when a game is restored
+    fn kind(&self) -> BotKind;
+
+    /// Picks the cards to play for the given prompt from the bot's hand
+    fn select_responses(
+        &mut self,
+        rng: &mut dyn RngCore,
+        prompt: &Prompt,
+        hand: &[CardID],
+    ) -> Vec<CardID>;
+
+    /// Picks which player's responses to vote for when every player judges the round
+    fn vote(&mut self, rng: &mut dyn RngCore, candidates: &[Uuid]) -> Option<Uuid>;
+}
+
+pub fn create_bot(kind: BotKind) -> Box<dyn Bot> {
+    match kind {
+        BotKind::Rando => Box::new(Rando),
+    }
+}
+
+/// Rando Cardrissian plays random cards every round
+pub struct Rando;
+
+impl Bot for Rando {
+    fn name(&self) -> String {
+        "Rando Cardrissian".to_owned()
+    }
+
+    fn kind(&self) -> BotKind {
+        BotKind::Rando
+    }
+
+    fn select_responses(
+        &mut self,
+        rng: &mut dyn RngCore,
+        prompt: &Prompt,
+        hand: &[CardID],
+    ) -> Vec<CardID> {
+        // Rando can't write, so blank cards stay in his hand
+        let playable = hand
+            .iter()
+            .copied()
+            .filter(|card| !card.is_blank())
+            .collect::<Vec<_>>();
+
+        playable
+            .choose_multiple(rng, prompt.pick as usize)
+            .copied()
+            .collect()
+    }
+
+    fn vote(&mut self, rng: &mut dyn RngCore, candidates: &[Uuid]) -> Option<Uuid> {
+        candidates.choose(rng).copied()
+    }
+}
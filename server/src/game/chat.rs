@@ -0,0 +1,153 @@
+use super::rate_limit::RateLimiter;
+use common::{
+    data::text::sanitize,
+    protocol::{clientbound::ChatMessage, MAX_CHAT_LENGTH},
+};
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// Where the optional list of filtered words is read from, one word per line
+pub const WORD_FILTER_FILE: &str = "./chat_filter.txt";
+/// How many messages are kept to show clients who join
+const HISTORY_LENGTH: usize = 50;
+/// How many messages a client can send within the rate limit window
+const RATE_LIMIT_MESSAGES: usize = 5;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(10);
+
+static WORD_FILTER: OnceCell<HashSet<String>> = OnceCell::new();
+
+/// The chat of the lobby or a single game
+pub struct ChatRoom {
+    history: VecDeque<ChatMessage>,
+    rate_limiter: RateLimiter,
+    muted: HashSet<Uuid>,
+}
+
+impl ChatRoom {
+    pub fn new() -> Self {
+        ChatRoom {
+            history: VecDeque::new(),
+            rate_limiter: RateLimiter::new(RATE_LIMIT_MESSAGES, RATE_LIMIT_WINDOW),
+            muted: HashSet::new(),
+        }
+    }
+
+    /// Checks and cleans up a message, adding it to the history. Returns the reason the message
+    /// was refused if it can't be sent.
+    pub fn post(&mut self, sender: Uuid, name: &str, text: &str) -> Result<ChatMessage, String> {
+        if self.muted.contains(&sender) {
+            return Err("You've been muted".to_owned());
+        }
+
+        let text = sanitize(text, MAX_CHAT_LENGTH)?;
+
+        if !self.rate_limiter.allow(sender) {
+            return Err("You're sending messages too quickly".to_owned());
+        }
+
+        let message = ChatMessage {
+            sender,
+            name: name.to_owned(),
+            text: filter_words(&text),
+            time: chrono::Utc::now().timestamp_millis(),
+        };
+
+        self.history.push_back(message.clone());
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+
+        Ok(message)
+    }
+
+    pub fn history(&self) -> Vec<ChatMessage> {
+        self.history.iter().cloned().collect()
+    }
+
+    pub fn is_muted(&self, id: Uuid) -> bool {
+        self.muted.contains(&id)
+    }
+
+    pub fn set_muted(&mut self, id: Uuid, muted: bool) {
+        if muted {
+            self.muted.insert(id);
+        } else {
+            self.muted.remove(&id);
+        }
+    }
+
+    /// Carries a client's mute over to their new connection
+    pub fn rekey(&mut self, old_id: Uuid, new_id: Uuid) {
+        if self.muted.remove(&old_id) {
+            self.muted.insert(new_id);
+        }
+        self.rate_limiter.rekey(old_id, new_id);
+    }
+
+    /// Drops everything kept about a client who left
+    pub fn forget(&mut self, id: Uuid) {
+        self.rate_limiter.forget(id);
+        self.muted.remove(&id);
+    }
+
+    /// Drops everything kept about clients who aren't around anymore
+    pub fn retain(&mut self, mut present: impl FnMut(Uuid) -> bool) {
+        self.rate_limiter.retain(&mut present);
+        self.muted.retain(|&id| present(id));
+    }
+}
+
+/// Replaces every filtered word in the text with asterisks, ignoring case
+fn filter_words(text: &str) -> String {
+    let filter = WORD_FILTER.get_or_init(load_word_filter);
+    if filter.is_empty() {
+        return text.to_owned();
+    }
+
+    let mut filtered = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+
+        if filter.contains(&word.to_lowercase()) {
+            filtered.extend(word.chars().map(|_| '*'));
+        } else {
+            filtered.push_str(&word);
+        }
+        word.clear();
+        filtered.push(c);
+    }
+
+    // Drop the space added to finish the last word
+    filtered.pop();
+    filtered
+}
+
+fn load_word_filter() -> HashSet<String> {
+    let list = match fs::read_to_string(WORD_FILTER_FILE) {
+        Ok(list) => list,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to read chat word filter: {}", e);
+            }
+            return HashSet::new();
+        }
+    };
+
+    let words = list
+        .lines()
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<HashSet<_>>();
+    info!("Loaded {} filtered chat words", words.len());
+    words
+}
@@ -0,0 +1,67 @@
+use common::data::cards::CardID;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A shuffled pile of cards to draw from, along with the cards that have been played.
+///
+/// Cards which have been drawn but not discarded yet are in play, either in a hand or on the
+/// table, and are never reshuffled back into the draw pile.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Deck {
+    draw_pile: Vec<CardID>,
+    discard_pile: Vec<CardID>,
+    in_play: HashSet<CardID>,
+}
+
+impl Deck {
+    /// Creates a deck out of every given card, shuffled
+    pub fn new<R: Rng + ?Sized>(mut cards: Vec<CardID>, rng: &mut R) -> Self {
+        cards.shuffle(rng);
+
+        Deck {
+            draw_pile: cards,
+            discard_pile: Vec::new(),
+            in_play: HashSet::new(),
+        }
+    }
+
+    /// Creates a deck with no cards in it
+    pub fn empty() -> Self {
+        Deck {
+            draw_pile: Vec::new(),
+            discard_pile: Vec::new(),
+            in_play: HashSet::new(),
+        }
+    }
+
+    /// Draws the top card, shuffling the discard pile into the draw pile if it ran out. Returns
+    /// `None` if every card is in play.
+    pub fn draw<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<CardID> {
+        if self.draw_pile.is_empty() {
+            self.reshuffle(rng);
+        }
+
+        let card = self.draw_pile.pop()?;
+        self.in_play.insert(card);
+        Some(card)
+    }
+
+    /// Puts a card which was in play onto the discard pile
+    pub fn discard(&mut self, card: CardID) {
+        // Ignore cards which were never drawn from this deck so they can't be duplicated
+        if self.in_play.remove(&card) {
+            self.discard_pile.push(card);
+        }
+    }
+
+    /// Whether the draw pile has run out, ignoring any cards that could be reshuffled
+    pub fn is_exhausted(&self) -> bool {
+        self.draw_pile.is_empty()
+    }
+
+    fn reshuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.draw_pile.append(&mut self.discard_pile);
+        self.draw_pile.shuffle(rng);
+    }
+}
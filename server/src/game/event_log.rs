@@ -0,0 +1,94 @@
+use common::data::events::{GameEvent, LoggedEvent, ReplayState};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+/// Where the logs of finished games are saved
+pub const HISTORY_DIR: &str = "./history";
+
+/// An append-only record of everything that happened in a game
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EventLog {
+    events: Vec<LoggedEvent>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        EventLog { events: Vec::new() }
+    }
+
+    pub fn record(&mut self, event: GameEvent) {
+        self.events.push(LoggedEvent {
+            time: chrono::Utc::now().timestamp_millis(),
+            event,
+        });
+    }
+
+    /// Writes the log to the history directory as JSON Lines, replacing any earlier save
+    pub fn save(&self, game_id: Uuid) -> Result<(), String> {
+        let mut lines = String::new();
+        for event in self.events.iter() {
+            match serde_json::to_string(event) {
+                Ok(line) => lines.push_str(&line),
+                Err(e) => return Err(format!("Error serializing event: {}", e)),
+            }
+            lines.push('\n');
+        }
+
+        fs::create_dir_all(HISTORY_DIR)
+            .and_then(|_| fs::write(log_path(game_id), lines))
+            .map_err(|e| format!("Error writing game log: {}", e))
+    }
+}
+
+/// Reads the saved log of a past game. Logs of unlisted games are never read back, since their
+/// ids are enough to join them.
+pub fn load_log(game_id: Uuid) -> Result<Vec<LoggedEvent>, String> {
+    let lines = match fs::read_to_string(log_path(game_id)) {
+        Ok(lines) => lines,
+        Err(e) => return Err(format!("Error reading game log: {}", e)),
+    };
+
+    let events = lines
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| format!("Error deserializing event: {}", e))
+        })
+        .collect::<Result<Vec<LoggedEvent>, String>>()?;
+
+    let unlisted = ReplayState::replay(&events, events.len())
+        .settings
+        .is_none_or(|settings| settings.unlisted);
+    if unlisted {
+        return Err(format!("Game {} is unlisted", game_id));
+    }
+
+    Ok(events)
+}
+
+/// The ids of every listed game with a saved log
+pub fn saved_games() -> Vec<Uuid> {
+    let entries = match fs::read_dir(HISTORY_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "jsonl" {
+                return None;
+            }
+            Uuid::parse_str(path.file_stem()?.to_str()?).ok()
+        })
+        .filter(|&game_id| load_log(game_id).is_ok())
+        .collect()
+}
+
+fn log_path(game_id: Uuid) -> PathBuf {
+    Path::new(HISTORY_DIR).join(format!("{}.jsonl", game_id))
+}
@@ -1,4 +1,14 @@
-use super::packs::PackStore;
+use super::{
+    bots::{create_bot, Bot},
+    chat::ChatRoom,
+    deck::Deck,
+    event_log::EventLog,
+    packs::PackStore,
+    password::Password,
+    rate_limit::RateLimiter,
+    settings::validate_setting,
+    snapshot::GameSnapshot,
+};
 use crate::{
     network::{client::ClientHandler, Listener, NetworkHandler},
     LOBBY_ID,
@@ -6,41 +16,140 @@ use crate::{
 use async_trait::async_trait;
 use common::{
     data::{
-        cards::{CardID, Pack, Prompt, Response},
+        cards::{CardID, Pack, Prompt, Response, MAX_WRITE_IN_LENGTH},
+        events::GameEvent,
+        text::{sanitize, sanitize_name},
         VecMap,
     },
     protocol::{
-        clientbound::{ClientBoundPacket, PacketResponse, ResponseData},
+        clientbound::{
+            ChatScope,
+            ClientBoundPacket,
+            Combo,
+            GameSummary,
+            PacketResponse,
+            PlayerStats,
+            ResponseData,
+            RoundRecord,
+            Submission,
+        },
         serverbound::ServerBoundPacket,
+        CzarRotation,
+        EndCondition,
+        GamePhase,
         GameSetting,
         GameSettings,
+        JudgingMode,
+        LateJoin,
+        ReactionTarget,
+        ServerListEntry,
+        TieBreak,
+        MAX_NAME_LENGTH,
+        PASSWORD_REQUIRED,
     },
 };
-use log::error;
-use rand::{thread_rng, Rng};
+use futures::future::{FutureExt, LocalBoxFuture};
+use log::{error, info};
+use rand::{seq::SliceRandom, thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::{
     cell::{RefCell, RefMut},
-    collections::HashMap,
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
     rc::Rc,
     sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 use tokio::sync::MutexGuard;
 use uuid::Uuid;
 
+/// The custom pack that winning write-ins are saved to
+const WRITE_IN_PACK: &str = "Write-ins";
+/// The most bots a host can add to a game
+const MAX_BOTS: usize = 3;
+/// How long players have to rejoin a restored game before they're removed from it
+const REJOIN_GRACE_PERIOD: Duration = Duration::from_secs(120);
+/// How long players have to act before missing a deadline, if there's no max selection time
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(60);
+/// How many reactions a client can send within the reaction rate limit window
+const REACTION_LIMIT: usize = 5;
+const REACTION_LIMIT_WINDOW: Duration = Duration::from_secs(5);
+
 pub struct Game {
     pub id: Uuid,
+    // Short code players can share to invite others, assigned by the lobby
+    pub code: String,
     pack_store: Arc<RwLock<PackStore>>,
     players: VecMap<Uuid, Player>,
+    // Clients watching a game they joined late
+    spectators: HashSet<Uuid>,
+    // Late joiners waiting to be dealt in when the next round starts
+    queued: VecMap<Uuid, Player>,
+    // Kept in a BTreeMap so bots always act in the same order for a given seed
+    bots: BTreeMap<Uuid, Box<dyn Bot>>,
     host_id: Uuid,
     packs: Vec<Arc<Pack>>,
-    available_prompts: Vec<CardID>,
-    available_responses: Vec<CardID>,
+    prompts: Deck,
+    responses: Deck,
     state: GameState,
     pub max_players: Option<usize>,
     max_selection_time: Option<u32>,
     points_to_win: u32,
+    blank_cards: usize,
+    save_write_ins: bool,
+    rebooting_the_universe: bool,
+    packing_heat: bool,
+    judging_mode: JudgingMode,
+    vote_tie_break: TieBreak,
+    hand_size: usize,
+    end_condition: EndCondition,
+    end_tie_break: TieBreak,
+    czar_rotation: CzarRotation,
+    pause_timeout: u32,
+    skip_vote_threshold: Option<u32>,
+    afk_threshold: u32,
+    kick_afk: bool,
+    late_join: LateJoin,
+    late_join_handicap: bool,
+    password: Option<Password>,
+    unlisted: bool,
+    title: String,
     czar_index: usize,
+    // The czar of the last round, who shouldn't judge twice in a row
+    last_czar: Option<Uuid>,
+    // Everyone who has judged since the random rotation last started over
+    past_czars: HashSet<Uuid>,
+    round_winners: Vec<Uuid>,
+    round: u32,
+    started_at: Option<Instant>,
+    round_started_at: Option<Instant>,
+    // When the host paused the game, if it's paused
+    paused_at: Option<Instant>,
+    // The round and phase being timed for AFK players, and when the current deadline started
+    deadline: Option<(u32, PlayingState, Instant)>,
+    history: Vec<RoundRecord>,
+    // Maps each voter to the player whose responses they voted for
+    votes: HashMap<Uuid, Uuid>,
+    // Players who voted to skip the current prompt
+    skip_votes: HashSet<Uuid>,
+    // How many reactions each submission got this round, by the player who played it
+    reactions: HashMap<Uuid, u32>,
+    reaction_limiter: RateLimiter,
+    chat: ChatRoom,
+    // Whether the czar already used their redraw this round
+    prompt_redrawn: bool,
     current_prompt: Option<Prompt>,
+    current_prompt_card: Option<CardID>,
+    seed: u64,
+    // All randomness in the game comes from here so a game can be reproduced from its seed
+    rng: ChaCha12Rng,
+    event_log: EventLog,
+    // Players of a restored game who haven't reconnected yet
+    awaiting_rejoin: HashSet<Uuid>,
+    // Maps the connections rejoining the game to the players they're taking back
+    pending_rejoins: HashMap<Uuid, Uuid>,
+    restored_at: Option<Instant>,
 }
 
 impl Game {
@@ -52,72 +161,460 @@ impl Game {
     ) -> Result<Self, String> {
         let mut loaded_packs = Vec::new();
 
-        for pack_name in settings.packs {
-            loaded_packs.push(pack_store.write().unwrap().load_pack(&pack_name)?)
+        for pack_name in settings.packs.iter() {
+            loaded_packs.push(pack_store.write().unwrap().load_pack(pack_name)?)
         }
 
+        let seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+        let mut event_log = EventLog::new();
+        // Saved logs can be read by anyone, so they never include the password
+        event_log.record(GameEvent::GameCreated(GameSettings {
+            seed: Some(seed),
+            password: None,
+            ..settings.clone()
+        }));
+
         Ok(Game {
             id,
+            code: String::new(),
             pack_store,
             players: VecMap::new(),
+            spectators: HashSet::new(),
+            queued: VecMap::new(),
+            bots: BTreeMap::new(),
             host_id,
             packs: loaded_packs,
-            available_prompts: Vec::new(),
-            available_responses: Vec::new(),
+            prompts: Deck::empty(),
+            responses: Deck::empty(),
             state: GameState::WaitingToStart,
             max_players: settings.max_players,
             max_selection_time: settings.max_selection_time,
             points_to_win: settings.points_to_win,
+            blank_cards: settings.blank_cards,
+            save_write_ins: settings.save_write_ins,
+            rebooting_the_universe: settings.rebooting_the_universe,
+            packing_heat: settings.packing_heat,
+            judging_mode: settings.judging_mode,
+            vote_tie_break: settings.vote_tie_break,
+            hand_size: settings.hand_size,
+            end_condition: settings.end_condition,
+            end_tie_break: settings.end_tie_break,
+            czar_rotation: settings.czar_rotation,
+            pause_timeout: settings.pause_timeout,
+            skip_vote_threshold: settings.skip_vote_threshold,
+            afk_threshold: settings.afk_threshold,
+            kick_afk: settings.kick_afk,
+            late_join: settings.late_join,
+            late_join_handicap: settings.late_join_handicap,
+            password: settings.password.as_deref().map(Password::new),
+            unlisted: settings.unlisted,
+            title: settings.title.trim().to_owned(),
             czar_index: 0,
+            last_czar: None,
+            past_czars: HashSet::new(),
+            round_winners: Vec::new(),
+            round: 0,
+            started_at: None,
+            round_started_at: None,
+            paused_at: None,
+            deadline: None,
+            history: Vec::new(),
+            votes: HashMap::new(),
+            skip_votes: HashSet::new(),
+            reactions: HashMap::new(),
+            reaction_limiter: RateLimiter::new(REACTION_LIMIT, REACTION_LIMIT_WINDOW),
+            chat: ChatRoom::new(),
+            prompt_redrawn: false,
             current_prompt: None,
+            current_prompt_card: None,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            event_log,
+            awaiting_rejoin: HashSet::new(),
+            pending_rejoins: HashMap::new(),
+            restored_at: None,
+        })
+    }
+
+    /// The settings the game is currently using
+    fn settings(&self) -> GameSettings {
+        GameSettings {
+            max_players: self.max_players,
+            max_selection_time: self.max_selection_time,
+            points_to_win: self.points_to_win,
+            packs: self.packs.iter().map(|pack| pack.name.clone()).collect(),
+            blank_cards: self.blank_cards,
+            save_write_ins: self.save_write_ins,
+            rebooting_the_universe: self.rebooting_the_universe,
+            packing_heat: self.packing_heat,
+            judging_mode: self.judging_mode,
+            vote_tie_break: self.vote_tie_break,
+            hand_size: self.hand_size,
+            end_condition: self.end_condition,
+            end_tie_break: self.end_tie_break,
+            czar_rotation: self.czar_rotation,
+            pause_timeout: self.pause_timeout,
+            skip_vote_threshold: self.skip_vote_threshold,
+            afk_threshold: self.afk_threshold,
+            kick_afk: self.kick_afk,
+            late_join: self.late_join,
+            late_join_handicap: self.late_join_handicap,
+            // The password is never saved
+            password: None,
+            unlisted: self.unlisted,
+            title: self.title.clone(),
+            seed: Some(self.seed),
+        }
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            id: self.id,
+            code: self.code.clone(),
+            host_id: self.host_id,
+            settings: self.settings(),
+            players: self.players.to_vec(),
+            bots: self
+                .bots
+                .iter()
+                .map(|(id, bot)| (*id, bot.kind()))
+                .collect(),
+            prompts: self.prompts.clone(),
+            responses: self.responses.clone(),
+            state: self.state,
+            czar_index: self.czar_index,
+            last_czar: self.last_czar,
+            past_czars: self.past_czars.clone(),
+            round_winners: self.round_winners.clone(),
+            round: self.round,
+            millis_since_start: self
+                .started_at
+                .map(|start| start.elapsed().as_millis() as u64),
+            millis_since_round_start: self
+                .round_started_at
+                .map(|start| start.elapsed().as_millis() as u64),
+            millis_since_pause: self
+                .paused_at
+                .map(|start| start.elapsed().as_millis() as u64),
+            history: self.history.clone(),
+            votes: self.votes.clone(),
+            skip_votes: self.skip_votes.clone(),
+            reactions: self.reactions.clone(),
+            prompt_redrawn: self.prompt_redrawn,
+            current_prompt: self.current_prompt.clone(),
+            current_prompt_card: self.current_prompt_card,
+            event_log: self.event_log.clone(),
+            rng_word_pos: self.rng.get_word_pos(),
+            has_password: self.has_password(),
+        }
+    }
+
+    /// Recreates a game from a snapshot. Every player has to rejoin it within the grace period.
+    pub fn restore(
+        snapshot: GameSnapshot,
+        pack_store: Arc<RwLock<PackStore>>,
+    ) -> Result<Self, String> {
+        let mut game = Game::new(snapshot.id, snapshot.host_id, pack_store, snapshot.settings)?;
+        let now = Instant::now();
+
+        game.code = snapshot.code;
+
+        for (id, player) in snapshot.players {
+            game.players.insert(id, player);
+        }
+        game.bots = snapshot
+            .bots
+            .into_iter()
+            .map(|(id, kind)| (id, create_bot(kind)))
+            .collect();
+        game.prompts = snapshot.prompts;
+        game.responses = snapshot.responses;
+        game.state = snapshot.state;
+        game.czar_index = snapshot.czar_index;
+        game.last_czar = snapshot.last_czar;
+        game.past_czars = snapshot.past_czars;
+        game.round_winners = snapshot.round_winners;
+        game.round = snapshot.round;
+        game.started_at = snapshot
+            .millis_since_start
+            .and_then(|millis| now.checked_sub(Duration::from_millis(millis)));
+        game.round_started_at = snapshot
+            .millis_since_round_start
+            .and_then(|millis| now.checked_sub(Duration::from_millis(millis)));
+        game.paused_at = snapshot
+            .millis_since_pause
+            .and_then(|millis| now.checked_sub(Duration::from_millis(millis)));
+        game.history = snapshot.history;
+        game.votes = snapshot.votes;
+        game.skip_votes = snapshot.skip_votes;
+        game.reactions = snapshot.reactions;
+        game.prompt_redrawn = snapshot.prompt_redrawn;
+        game.current_prompt = snapshot.current_prompt;
+        game.current_prompt_card = snapshot.current_prompt_card;
+        game.event_log = snapshot.event_log;
+        if snapshot.has_password {
+            game.password = Some(Password::locked());
+        }
+
+        // The generator was already seeded by Game::new, so moving it to where the game left off
+        // continues the same sequence of numbers
+        game.rng.set_word_pos(snapshot.rng_word_pos);
+
+        game.awaiting_rejoin = game
+            .players
+            .iter()
+            .filter(|(_, player)| !player.is_bot)
+            .map(|(id, _)| *id)
+            .collect();
+        game.restored_at = Some(now);
+
+        Ok(game)
+    }
+
+    /// Lets a new connection take back a player's seat in a restored game, returning whether the
+    /// token matched a player who hasn't rejoined yet
+    pub fn expect_rejoin(&mut self, client_id: Uuid, token: Uuid) -> bool {
+        let player_id = self
+            .players
+            .iter()
+            .find(|(id, player)| player.rejoin_token == token && self.awaiting_rejoin.contains(id))
+            .map(|(id, _)| *id);
+
+        match player_id {
+            Some(player_id) => {
+                self.pending_rejoins.insert(client_id, player_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces every reference to a player's old id with their new connection's id
+    fn rekey_player(&mut self, old_id: Uuid, new_id: Uuid) {
+        let replace = |id: Uuid| if id == old_id { new_id } else { id };
+
+        if let Some((id, player)) = self.players.iter_mut().find(|(id, _)| *id == old_id) {
+            *id = new_id;
+            player.client_id = new_id;
+        }
+
+        self.host_id = replace(self.host_id);
+        self.last_czar = self.last_czar.map(replace);
+        self.past_czars = self.past_czars.iter().copied().map(replace).collect();
+        self.round_winners = self.round_winners.iter().copied().map(replace).collect();
+        self.skip_votes = self.skip_votes.iter().copied().map(replace).collect();
+        self.chat.rekey(old_id, new_id);
+        self.reaction_limiter.rekey(old_id, new_id);
+        if let Some(count) = self.reactions.remove(&old_id) {
+            self.reactions.insert(new_id, count);
+        }
+        self.votes = self
+            .votes
+            .iter()
+            .map(|(&voter, &candidate)| (replace(voter), replace(candidate)))
+            .collect();
+
+        for round in self.history.iter_mut() {
+            round.czar = round.czar.map(replace);
+            round.winners = round.winners.iter().copied().map(replace).collect();
+            for submission in round.submissions.iter_mut() {
+                submission.player = replace(submission.player);
+            }
+        }
+
+        self.event_log
+            .record(GameEvent::PlayerReconnected { old_id, new_id });
+    }
+
+    /// Moves a player of a restored game over to their new connection
+    async fn reattach(&mut self, network_handler: &mut NetworkHandler, old_id: Uuid, new_id: Uuid) {
+        self.awaiting_rejoin.remove(&old_id);
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        // Everyone else still knows the player by their old id
+        self.broadcast_to_players(&mut client_handler, &ClientBoundPacket::RemovePlayer {
+            id: old_id,
+            new_host: None,
         })
+        .await;
+
+        self.rekey_player(old_id, new_id);
+
+        let player = self.players.get(&new_id).unwrap();
+        self.broadcast_to_players(&mut client_handler, &player.as_packet())
+            .await;
+
+        let hand = player
+            .hand
+            .iter()
+            .map(|&card| ResponseData::new(card, self.response_text(card)))
+            .collect();
+        let packets = self.catch_up_packets(new_id, hand);
+        client_handler.send_packets(new_id, &packets).await;
+    }
+
+    /// Everything a client needs to catch up on the game, given the cards they should add to
+    /// their hand
+    fn catch_up_packets(&self, client_id: Uuid, hand: Vec<ResponseData>) -> Vec<ClientBoundPacket> {
+        let mut packets = self
+            .players
+            .values()
+            .map(Player::as_packet)
+            .collect::<Vec<_>>();
+        packets.extend(self.settings_as_packets());
+        packets.push(ClientBoundPacket::InviteCode(self.code.clone()));
+        packets.extend(
+            self.players
+                .iter()
+                .filter(|(_, player)| player.afk)
+                .map(|(id, _)| ClientBoundPacket::PlayerAfk { id: *id, afk: true }),
+        );
+        packets.extend(
+            self.players
+                .keys()
+                .filter(|&&id| self.chat.is_muted(id))
+                .map(|&id| ClientBoundPacket::ChatMuted { id, muted: true }),
+        );
+        packets.push(ClientBoundPacket::ChatHistory {
+            scope: ChatScope::Game,
+            messages: self.chat.history(),
+        });
+
+        if let Some(player) = self.players.get(&client_id) {
+            packets.push(ClientBoundPacket::RejoinToken {
+                game_id: self.id,
+                token: player.rejoin_token,
+            });
+        }
+
+        if let GameState::Playing(playing_state) = self.state {
+            if let Some(prompt) = self.current_prompt.clone() {
+                packets.push(ClientBoundPacket::NextRound {
+                    czar: self.czar_id(),
+                    prompt,
+                    new_responses: hand,
+                });
+            }
+
+            // Only players who finished picking have responses to show
+            if playing_state != PlayingState::PlayerSelection {
+                packets.push(self.display_responses());
+            }
+
+            if self.paused_at.is_some() {
+                packets.push(self.pause_state_packet());
+            }
+        }
+
+        packets
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Saves the event log to disk, logging any errors. Private games aren't saved since anyone
+    /// can read saved logs, and a game's id is enough to join it.
+    fn save_event_log(&self) {
+        if self.unlisted || self.has_password() {
+            return;
+        }
+
+        if let Err(e) = self.event_log.save(self.id) {
+            error!("Failed to save the log of game {}: {}", self.id, e);
+        }
     }
 
     pub fn host_name(&self) -> String {
         self.players.get(&self.host_id).unwrap().name.clone()
     }
 
-    fn initialize_prompts(&mut self) {
+    fn all_prompts(&self) -> Vec<CardID> {
+        let mut prompts = Vec::new();
         for (index, pack) in self.packs.iter().enumerate() {
-            self.available_prompts
-                .extend((0 .. pack.prompts.len()).map(|j| CardID::new(index, j)));
+            prompts.extend((0 .. pack.prompts.len()).map(|j| CardID::new(index, j)));
         }
+        prompts
     }
 
-    fn initialize_responses(&mut self) {
+    fn all_responses(&self) -> Vec<CardID> {
+        let mut responses = Vec::new();
         for (index, pack) in self.packs.iter().enumerate() {
-            self.available_responses
-                .extend((0 .. pack.responses.len()).map(|j| CardID::new(index, j)));
+            responses.extend((0 .. pack.responses.len()).map(|j| CardID::new(index, j)));
         }
+
+        responses.extend((0 .. self.blank_cards).map(CardID::blank));
+        responses
     }
 
+    /// Discards the previous prompt and draws the next one
     fn select_prompt(&mut self) -> Prompt {
-        if self.available_prompts.is_empty() {
-            self.initialize_prompts();
+        if let Some(previous) = self.current_prompt_card.take() {
+            self.prompts.discard(previous);
         }
 
-        let index = thread_rng().gen_range(0 .. self.available_prompts.len());
-        let card = self.available_prompts.remove(index);
+        // With the previous prompt discarded nothing is in play, and start_game makes sure the
+        // deck isn't empty, so there's always a prompt to draw
+        let card = self.prompts.draw(&mut self.rng).unwrap();
+        self.current_prompt_card = Some(card);
+
         self.packs[card.pack_number].prompts[card.card_number].clone()
     }
 
-    fn add_responses(&mut self, dest: &mut Vec<ResponseData>, count: usize) {
+    /// Draws responses into a player's hand, returning the cards drawn. Fewer cards are drawn if
+    /// every other card is already in play.
+    fn deal(&mut self, player_id: Uuid, count: usize) -> Vec<ResponseData> {
+        let mut responses = Vec::with_capacity(count);
         for _ in 0 .. count {
-            if self.available_responses.is_empty() {
-                self.initialize_responses();
+            match self.responses.draw(&mut self.rng) {
+                Some(card) => responses.push(ResponseData::new(card, self.response_text(card))),
+                None => break,
             }
+        }
+
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player
+                .hand
+                .extend(responses.iter().map(|response| response.id));
+        }
 
-            let index = thread_rng().gen_range(0 .. self.available_responses.len());
-            let card = self.available_responses.remove(index);
-            dest.push(ResponseData::new(
-                card,
-                self.packs[card.pack_number].responses[card.card_number].clone(),
-            ));
+        responses
+    }
+
+    /// The number of cards a player needs to refill their hand and draw any extra cards the
+    /// prompt calls for
+    fn cards_to_deal(&self, player_id: Uuid, prompt: &Prompt) -> usize {
+        let hand_size = self
+            .players
+            .get(&player_id)
+            .map(|player| player.hand.len())
+            .unwrap_or(0);
+        let refill = self.hand_size.saturating_sub(hand_size);
+        refill + self.extra_cards(player_id, prompt)
+    }
+
+    /// The number of cards a prompt lets a player draw on top of a full hand
+    fn extra_cards(&self, player_id: Uuid, prompt: &Prompt) -> usize {
+        // The czar doesn't pick responses so they don't need the extra cards
+        if Some(player_id) == self.czar_id() {
+            return 0;
         }
+
+        let packing_heat = if self.packing_heat && prompt.pick == 2 {
+            1
+        } else {
+            0
+        };
+        prompt.draw.unwrap_or(0) as usize + packing_heat
     }
 
     fn response_text(&self, card: CardID) -> Response {
-        self.packs[card.pack_number].responses[card.card_number].clone()
+        if card.is_blank() {
+            // The text of a blank card is only known once it's played
+            Response::new()
+        } else {
+            self.packs[card.pack_number].responses[card.card_number].clone()
+        }
     }
 
     fn display_responses(&self) -> ClientBoundPacket {
@@ -130,216 +627,1570 @@ impl Game {
             self.players
                 .iter()
                 .filter(|(_, player)| player.selections.len() == pick_num)
-                .map(|(id, player)| {
-                    (
-                        *id,
-                        player
-                            .selections
-                            .iter()
-                            .map(|&card| ResponseData {
-                                text: self.response_text(card),
-                                id: card,
-                            })
-                            .collect(),
-                    )
-                })
+                .map(|(id, player)| (*id, player.selections.clone()))
                 .collect(),
         )
     }
 
-    async fn next_round(&mut self, network_handler: &mut NetworkHandler) {
-        self.state = GameState::Playing(PlayingState::PlayerSelection);
+    fn save_winning_write_ins(&self, winner_id: Uuid) {
+        let write_ins = match self.players.get(&winner_id) {
+            Some(winner) => winner
+                .selections
+                .iter()
+                .filter(|response| response.id.is_blank())
+                .map(|response| response.text.clone())
+                .collect::<Vec<_>>(),
+            None => return,
+        };
 
-        for player in self.players.values_mut() {
-            player.selections.clear();
+        if write_ins.is_empty() {
+            return;
         }
 
-        let last_czar = self.czar_index;
-        self.czar_index = (self.czar_index + 1) % self.players.len();
+        if let Err(e) = self
+            .pack_store
+            .write()
+            .unwrap()
+            .add_responses(WRITE_IN_PACK, write_ins)
+        {
+            error!("Failed to save write-ins: {}", e);
+        }
+    }
 
-        let add_cards = self
-            .current_prompt
-            .as_ref()
-            .map(|prompt| prompt.pick as usize)
-            .unwrap_or(0);
-        let prompt = self.select_prompt();
+    /// The number of votes needed to skip the prompt, or `None` if skipping is disabled
+    fn skip_votes_needed(&self) -> Option<usize> {
+        let percent = self.skip_vote_threshold? as usize;
+        // Bots don't vote
+        let voters = self
+            .players
+            .values()
+            .filter(|player| !player.is_bot)
+            .count();
+        Some((voters * percent).div_ceil(100).max(1))
+    }
 
-        let mut client_handler = network_handler.client_handler.lock().await;
-        for (id, _player) in self.players.clone().iter() {
-            let mut responses = Vec::with_capacity(add_cards);
-            if id != &self.players[last_czar].0 {
-                self.add_responses(&mut responses, add_cards);
-            }
+    async fn redraw_prompt(
+        &mut self,
+        network_handler: &mut NetworkHandler,
+        player_id: Uuid,
+    ) -> PacketResponse {
+        if Some(player_id) != self.czar_id() {
+            return PacketResponse::RejectedWithReason(
+                "Only the czar can redraw the prompt".to_owned(),
+            );
+        }
 
-            let round_data = ClientBoundPacket::NextRound {
-                czar: self.players[self.czar_index].0,
-                prompt: prompt.clone(),
-                new_responses: responses,
-            };
+        if self.prompt_redrawn {
+            return PacketResponse::RejectedWithReason(
+                "The prompt can only be redrawn once per round".to_owned(),
+            );
+        }
 
-            client_handler.send_packet(*id, &round_data).await;
+        // Bots pick as soon as the round starts, so only players' picks count
+        if self
+            .players
+            .values()
+            .any(|player| !player.is_bot && !player.selections.is_empty())
+        {
+            return PacketResponse::RejectedWithReason(
+                "The prompt can't be redrawn once someone has picked".to_owned(),
+            );
         }
 
-        self.current_prompt = Some(prompt);
+        self.prompt_redrawn = true;
+        self.replace_prompt(network_handler).await;
+        PacketResponse::Accepted
     }
 
-    async fn broadcast_to_players<'a>(
-        &self,
-        client_handler: &mut MutexGuard<'a, ClientHandler>,
-        packet: &ClientBoundPacket,
-    ) {
-        client_handler
-            .broadcast(
-                &packet,
-                |client| self.players.contains_key(&client.id),
-                |_| {},
+    async fn vote_to_skip(
+        &mut self,
+        network_handler: &mut NetworkHandler,
+        player_id: Uuid,
+    ) -> PacketResponse {
+        let needed = match self.skip_votes_needed() {
+            Some(needed) => needed,
+            None =>
+                return PacketResponse::RejectedWithReason(
+                    "Skipping prompts is not enabled".to_owned(),
+                ),
+        };
+
+        if !self.players.contains_key(&player_id) || !self.skip_votes.insert(player_id) {
+            return PacketResponse::Rejected;
+        }
+
+        if self.skip_votes.len() >= needed {
+            self.replace_prompt(network_handler).await;
+        } else {
+            self.broadcast_to_players(
+                &mut network_handler.client_handler.lock().await,
+                &ClientBoundPacket::SkipVotes {
+                    votes: self.skip_votes.len(),
+                    needed,
+                },
             )
             .await;
-    }
-
-    fn settings_as_packets(&self) -> Vec<ClientBoundPacket> {
-        let mut packets = Vec::with_capacity(2 + self.packs.len());
-        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::MaxPlayers(
-            self.max_players,
-        )));
-        packets.push(ClientBoundPacket::SettingUpdate(
-            GameSetting::MaxSelectionTime(self.max_selection_time),
-        ));
-        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::PointsToWin(
-            self.points_to_win,
-        )));
-        for pack in self.packs.iter() {
-            packets.push(ClientBoundPacket::SettingUpdate(GameSetting::AddPack(
-                pack.name.clone(),
-            )));
         }
-        packets
-    }
 
-    pub fn num_players(&self) -> usize {
-        self.players.len()
+        PacketResponse::Accepted
     }
-}
-
-#[async_trait(?Send)]
-impl Listener for Game {
-    async fn client_connected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
-        let set_host = self.players.is_empty();
-        if set_host {
-            self.host_id = client_id;
-        }
-        let player = Player::new(client_id, set_host);
-        let packet = player.as_packet();
 
-        let mut client_handler = network_handler.client_handler.lock().await;
-        self.broadcast_to_players(&mut client_handler, &packet)
-            .await;
+    /// Swaps the prompt for a new one without ending the round. Unlike cancelling a round, every
+    /// submission goes back to its player's hand, and hands are topped up or trimmed to the extra
+    /// cards the new prompt allows.
+    async fn replace_prompt(&mut self, network_handler: &mut NetworkHandler) {
+        self.skip_votes.clear();
+        self.reactions.clear();
+        self.deadline = None;
 
-        self.players.insert(client_id, player);
+        let old_prompt = self.current_prompt.take();
+        let prompt = self.select_prompt();
+        self.current_prompt = Some(prompt.clone());
+        self.event_log
+            .record(GameEvent::PromptReplaced(prompt.clone()));
+
+        for index in 0 .. self.players.len() {
+            let id = self.players[index].0;
+            let old_extra = old_prompt
+                .as_ref()
+                .map_or(0, |old_prompt| self.extra_cards(id, old_prompt));
+            let new_extra = self.extra_cards(id, &prompt);
+
+            // The newest cards are at the end of the hand, so those are the ones taken back
+            let player = &mut self.players[index].1;
+            let keep = player
+                .hand
+                .len()
+                .saturating_sub(old_extra.saturating_sub(new_extra));
+            let taken_back = player.hand.split_off(keep);
+            player.pick_time = None;
+            let selections = std::mem::take(&mut player.selections);
+            player
+                .hand
+                .extend(selections.into_iter().map(|selection| selection.id));
+            for card in taken_back {
+                self.responses.discard(card);
+            }
 
-        let mut packets = self
-            .players
-            .values()
-            .map(Player::as_packet)
-            .collect::<Vec<_>>();
-        packets.extend(self.settings_as_packets());
+            self.deal(id, new_extra.saturating_sub(old_extra));
+        }
 
-        if let GameState::Playing(playing_state) = self.state {
-            let mut responses = Vec::with_capacity(10);
-            self.add_responses(&mut responses, 10);
-            if let Some(prompt) = self.current_prompt.as_ref() {
-                packets.push(ClientBoundPacket::NextRound {
-                    czar: self.players[self.czar_index].0,
+        // Every hand is sent in full, so the new cards don't need to be sent separately
+        let mut client_handler = network_handler.client_handler.lock().await;
+        for (id, player) in self.players.iter() {
+            let hand = player
+                .hand
+                .iter()
+                .map(|&card| ResponseData::new(card, self.response_text(card)))
+                .collect();
+
+            client_handler
+                .send_packets(*id, &[
+                    ClientBoundPacket::NextRound {
+                        czar: self.czar_id(),
+                        prompt: prompt.clone(),
+                        new_responses: Vec::new(),
+                    },
+                    ClientBoundPacket::NewHand(hand),
+                ])
+                .await;
+        }
+        for id in self.watchers() {
+            client_handler
+                .send_packet(id, &ClientBoundPacket::NextRound {
+                    czar: self.czar_id(),
                     prompt: prompt.clone(),
-                    new_responses: responses,
-                });
-            }
-
-            if playing_state == PlayingState::CzarSelection {
-                let responses = self
-                    .players
-                    .iter()
-                    .map(|(id, player)| {
-                        (
-                            *id,
-                            player
-                                .selections
-                                .iter()
-                                .copied()
-                                .map(|card| ResponseData {
-                                    text: self.response_text(card),
-                                    id: card,
-                                })
-                                .collect::<Vec<_>>(),
-                        )
-                    })
-                    .collect::<HashMap<_, _>>();
-                packets.push(ClientBoundPacket::DisplayResponses(responses));
-            }
+                    new_responses: Vec::new(),
+                })
+                .await;
         }
+        drop(client_handler);
 
-        client_handler.send_packets(client_id, &packets).await;
+        self.round_started_at = Some(Instant::now());
+        self.play_bots(network_handler).await;
     }
 
-    async fn client_disconnected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
-        // If the final player is leaving
-        if self.players.len() == 1 {
-            self.players.remove(&client_id);
-            self.state = GameState::End;
-            return;
+    /// Trades one of a player's points for a fresh hand
+    async fn reboot_the_universe(
+        &mut self,
+        network_handler: &mut NetworkHandler,
+        player_id: Uuid,
+    ) -> PacketResponse {
+        if !self.rebooting_the_universe {
+            return PacketResponse::RejectedWithReason(
+                "Rebooting the Universe is not enabled".to_owned(),
+            );
         }
 
-        // Cancel the round if the czar left
-        let skip_round = client_id == self.players[self.czar_index].0;
-
-        let index = self
-            .players
-            .iter()
-            .enumerate()
-            .find(|p| p.1 .0 == client_id)
-            .unwrap()
-            .0;
-        let player = match self.players.remove(&client_id) {
+        let player = match self.players.get_mut(&player_id) {
             Some(player) => player,
-            None => return,
+            None => return PacketResponse::Rejected,
         };
 
-        if self.czar_index > index {
-            self.czar_index -= 1;
+        if player.points == 0 {
+            return PacketResponse::RejectedWithReason(
+                "You need a point to reboot the universe".to_owned(),
+            );
         }
 
-        let new_host = if player.is_host {
-            self.players
-                .first_mut()
-                .map(|(id, player)| {
-                    player.is_host = true;
-                    id
-                })
-                .copied()
-        } else {
-            None
-        };
+        if !player.selections.is_empty() {
+            return PacketResponse::RejectedWithReason(
+                "You can't reboot the universe after picking a card".to_owned(),
+            );
+        }
 
-        if let Some(host_id) = new_host {
-            self.host_id = host_id;
+        player.points -= 1;
+        let points = player.points;
+        let old_hand = std::mem::take(&mut player.hand);
+
+        // Draw the new hand before discarding the old one so none of the same cards come back
+        let new_hand = self.deal(player_id, old_hand.len());
+        for card in old_hand {
+            self.responses.discard(card);
         }
 
-        let packet = ClientBoundPacket::RemovePlayer {
-            id: player.client_id,
-            new_host,
-        };
+        self.event_log.record(GameEvent::PointsChanged {
+            id: player_id,
+            points,
+        });
 
         let mut client_handler = network_handler.client_handler.lock().await;
-        self.broadcast_to_players(&mut client_handler, &packet)
+        client_handler
+            .send_packet(player_id, &ClientBoundPacket::NewHand(new_hand))
             .await;
+        self.broadcast_to_players(&mut client_handler, &ClientBoundPacket::UpdatePoints {
+            id: player_id,
+            points,
+        })
+        .await;
 
-        if skip_round {
-            self.czar_index %= self.players.len();
-            self.broadcast_to_players(&mut client_handler, &ClientBoundPacket::CancelRound)
-                .await;
-            drop(client_handler);
-            self.current_prompt = None;
-            self.next_round(network_handler).await;
+        PacketResponse::Accepted
+    }
+
+    /// The player judging this round, if the game has a czar
+    fn czar_id(&self) -> Option<Uuid> {
+        match self.judging_mode {
+            JudgingMode::Czar => Some(self.players[self.czar_index].0),
+            JudgingMode::Democracy => None,
+        }
+    }
+
+    /// Finds the first player after `index` who is allowed to be czar, if anyone is
+    fn next_czar_index(&self, index: usize) -> Option<usize> {
+        (1 ..= self.players.len())
+            .map(|offset| (index + offset) % self.players.len())
+            .find(|&i| !self.players[i].1.is_bot && !self.players[i].1.afk)
+    }
+
+    /// The indices of the players who can judge the next round
+    fn czar_candidates(&self) -> Vec<usize> {
+        (0 .. self.players.len())
+            .filter(|&i| {
+                let (id, player) = &self.players[i];
+                !player.is_bot && !player.afk && Some(*id) != self.last_czar
+            })
+            .collect()
+    }
+
+    /// Picks the czar for the next round according to the rotation setting, returning whether
+    /// anyone was able to judge
+    fn rotate_czar(&mut self) -> bool {
+        let index = match self.czar_rotation {
+            CzarRotation::Sequential => None,
+            CzarRotation::WinnerJudges => {
+                let winners = self
+                    .czar_candidates()
+                    .into_iter()
+                    .filter(|&i| self.round_winners.contains(&self.players[i].0))
+                    .collect::<Vec<_>>();
+                winners.choose(&mut self.rng).copied()
+            }
+            CzarRotation::Random => self.random_czar_index(),
+        };
+
+        // Fall back to the next player in order if the rotation couldn't pick anyone
+        match index.or_else(|| self.next_czar_index(self.czar_index)) {
+            Some(index) => {
+                self.set_czar(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks a random player who hasn't judged since everyone last had a turn
+    fn random_czar_index(&mut self) -> Option<usize> {
+        let mut candidates = self
+            .czar_candidates()
+            .into_iter()
+            .filter(|&i| !self.past_czars.contains(&self.players[i].0))
+            .collect::<Vec<_>>();
+
+        // Start over once everyone has had a turn
+        if candidates.is_empty() {
+            self.past_czars.clear();
+            candidates = self.czar_candidates();
+        }
+
+        candidates.choose(&mut self.rng).copied()
+    }
+
+    fn set_czar(&mut self, index: usize) {
+        let czar = self.players[index].0;
+        self.czar_index = index;
+        self.last_czar = Some(czar);
+        self.past_czars.insert(czar);
+    }
+
+    fn pause_timeout(&self) -> Duration {
+        Duration::from_secs(self.pause_timeout as u64 * 60)
+    }
+
+    fn pause_state_packet(&self) -> ClientBoundPacket {
+        ClientBoundPacket::PauseState {
+            paused: self.paused_at.is_some(),
+            resumes_in: self
+                .paused_at
+                .map(|paused_at| {
+                    self.pause_timeout()
+                        .saturating_sub(paused_at.elapsed())
+                        .as_secs()
+                })
+                .unwrap_or(0),
+        }
+    }
+
+    async fn set_paused(&mut self, network_handler: &mut NetworkHandler, paused: bool) {
+        if paused == self.paused_at.is_some() {
+            return;
+        }
+
+        if paused {
+            self.paused_at = Some(Instant::now());
+            self.event_log.record(GameEvent::GamePaused);
+        } else if let Some(paused_at) = self.paused_at.take() {
+            // Timers don't count the time spent paused
+            unfreeze(&mut self.started_at, paused_at);
+            unfreeze(&mut self.round_started_at, paused_at);
+            self.deadline = None;
+            self.event_log.record(GameEvent::GameResumed);
+        }
+
+        let packet = self.pause_state_packet();
+        self.broadcast_to_players(&mut network_handler.client_handler.lock().await, &packet)
+            .await;
+
+        if !paused {
+            self.resume_round(network_handler).await;
+        }
+    }
+
+    /// Catches the round up on players who left while the game was paused, or starts it if it
+    /// was waiting on a czar
+    async fn resume_round(&mut self, network_handler: &mut NetworkHandler) {
+        let czar_left = matches!(self.judging_mode, JudgingMode::Czar)
+            && self
+                .last_czar
+                .is_some_and(|czar| !self.players.contains_key(&czar));
+
+        match self.state {
+            GameState::Playing(_) if self.current_prompt.is_none() =>
+                self.next_round(network_handler).await,
+            GameState::Playing(_) if czar_left => self.cancel_round(network_handler).await,
+            GameState::Playing(PlayingState::PlayerSelection) =>
+                self.check_all_selected(network_handler).await,
+            GameState::Playing(PlayingState::Voting) => self.check_all_voted(network_handler).await,
+            _ => {}
+        }
+    }
+
+    fn deadline_length(&self) -> Duration {
+        self.max_selection_time
+            .map(|seconds| Duration::from_secs(seconds as u64))
+            .unwrap_or(DEFAULT_DEADLINE)
+    }
+
+    /// The players the current phase of the round is waiting on
+    fn waiting_on(&self, playing_state: PlayingState) -> Vec<Uuid> {
+        let czar_id = self.czar_id();
+        let pick_num = self
+            .current_prompt
+            .as_ref()
+            .map(|prompt| prompt.pick as usize)
+            .unwrap_or(1);
+        let candidates = self.vote_candidates();
+
+        self.players
+            .iter()
+            .filter(|(id, player)| {
+                // Players who haven't rejoined a restored game can't be waited on either
+                if player.is_bot || player.afk || self.awaiting_rejoin.contains(id) {
+                    return false;
+                }
+
+                match playing_state {
+                    PlayingState::PlayerSelection =>
+                        Some(*id) != czar_id && player.selections.len() < pick_num,
+                    PlayingState::CzarSelection => Some(*id) == czar_id,
+                    PlayingState::Voting =>
+                        !self.votes.contains_key(id)
+                            && candidates.iter().any(|candidate| candidate != id),
+                }
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Counts a missed deadline against everyone the round has been waiting on since the last
+    /// deadline, marking players AFK once they've missed too many in a row
+    async fn check_deadline(&mut self, network_handler: &mut NetworkHandler) {
+        let playing_state = match self.state {
+            GameState::Playing(playing_state) if self.paused_at.is_none() => playing_state,
+            _ => return,
+        };
+
+        let now = Instant::now();
+        let length = self.deadline_length();
+        match self.deadline {
+            Some((round, state, started)) if round == self.round && state == playing_state =>
+                if now.duration_since(started) < length {
+                    return;
+                },
+            // Start timing whenever the round moves on
+            _ => {
+                self.deadline = Some((self.round, playing_state, now));
+                return;
+            }
+        }
+        self.deadline = Some((self.round, playing_state, now));
+
+        let mut gone_afk = Vec::new();
+        for id in self.waiting_on(playing_state) {
+            let player = match self.players.get_mut(&id) {
+                Some(player) => player,
+                None => continue,
+            };
+
+            // Players who are still sending packets are around, just slow
+            if player
+                .last_active
+                .is_some_and(|active| active.elapsed() < length)
+            {
+                continue;
+            }
+
+            player.missed_deadlines += 1;
+            if player.missed_deadlines >= self.afk_threshold {
+                gone_afk.push(id);
+            }
+        }
+
+        for id in gone_afk {
+            self.mark_afk(network_handler, id).await;
+        }
+    }
+
+    /// Stops waiting on a player who has gone idle, or sends them back to the lobby if AFK
+    /// players are kicked
+    async fn mark_afk(&mut self, network_handler: &mut NetworkHandler, player_id: Uuid) {
+        if self.kick_afk {
+            network_handler
+                .client_handler
+                .lock()
+                .await
+                .send_packet(
+                    player_id,
+                    &ClientBoundPacket::Kicked(
+                        "You were removed from the game for being AFK".to_owned(),
+                    ),
+                )
+                .await;
+            self.client_disconnected(network_handler, player_id).await;
+            network_handler
+                .forward_client(player_id, *LOBBY_ID.get().unwrap())
+                .await;
+            return;
+        }
+
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player.afk = true;
+        }
+
+        self.broadcast_to_players(
+            &mut network_handler.client_handler.lock().await,
+            &ClientBoundPacket::PlayerAfk {
+                id: player_id,
+                afk: true,
+            },
+        )
+        .await;
+
+        // The round might have only been waiting on them
+        match self.state {
+            GameState::Playing(PlayingState::PlayerSelection)
+            | GameState::Playing(PlayingState::CzarSelection)
+                if Some(player_id) == self.czar_id() =>
+                self.cancel_round(network_handler).await,
+            GameState::Playing(PlayingState::PlayerSelection) =>
+                self.check_all_selected(network_handler).await,
+            GameState::Playing(PlayingState::Voting) => self.check_all_voted(network_handler).await,
+            _ => {}
+        }
+    }
+
+    /// Notes that a player is still around, undoing them being marked AFK
+    async fn mark_active(&mut self, network_handler: &mut NetworkHandler, player_id: Uuid) {
+        let player = match self.players.get_mut(&player_id) {
+            Some(player) => player,
+            None => return,
+        };
+
+        player.last_active = Some(Instant::now());
+        player.missed_deadlines = 0;
+
+        if player.afk {
+            player.afk = false;
+            self.broadcast_to_players(
+                &mut network_handler.client_handler.lock().await,
+                &ClientBoundPacket::PlayerAfk {
+                    id: player_id,
+                    afk: false,
+                },
+            )
+            .await;
+        }
+    }
+
+    async fn start_game(&mut self, network_handler: &mut NetworkHandler) -> PacketResponse {
+        self.paused_at = None;
+        for (_, player) in self.players.iter_mut() {
+            player.points = 0;
+            player.selections = Vec::new();
+            player.hand = Vec::new();
+            player.pick_time = None;
+        }
+        self.history.clear();
+        self.votes.clear();
+
+        let prompts = self.all_prompts();
+        let responses = self.all_responses();
+
+        // This branch should never be taken
+        if prompts.is_empty() || responses.is_empty() {
+            return PacketResponse::RejectedWithReason("No packs selected".to_owned());
+        }
+
+        self.prompts = Deck::new(prompts, &mut self.rng);
+        self.responses = Deck::new(responses, &mut self.rng);
+        self.current_prompt_card = None;
+
+        self.event_log.record(GameEvent::GameStarted);
+
+        // Select the first czar at random
+        self.last_czar = None;
+        self.past_czars.clear();
+        self.round_winners.clear();
+        let index = self.random_czar_index().unwrap_or(0);
+        self.set_czar(index);
+        self.round = 1;
+        self.started_at = Some(Instant::now());
+
+        // Select the prompt
+        let prompt = self.select_prompt();
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        for index in 0 .. self.players.len() {
+            let id = self.players[index].0;
+            let count = self.cards_to_deal(id, &prompt);
+            let round_data = ClientBoundPacket::NextRound {
+                czar: self.czar_id(),
+                prompt: prompt.clone(),
+                new_responses: self.deal(id, count),
+            };
+
+            client_handler
+                .send_packets(id, &[ClientBoundPacket::StartGame, round_data])
+                .await;
+        }
+        drop(client_handler);
+
+        self.round_started_at = Some(Instant::now());
+        self.event_log.record(GameEvent::RoundStarted {
+            round: self.round,
+            czar: self.czar_id(),
+            prompt: prompt.clone(),
+        });
+        self.current_prompt = Some(prompt);
+        self.state = GameState::Playing(PlayingState::PlayerSelection);
+        self.play_bots(network_handler).await;
+
+        PacketResponse::Accepted
+    }
+
+    /// Has every bot pick its responses for the current prompt
+    async fn play_bots(&mut self, network_handler: &mut NetworkHandler) {
+        let prompt = match self.current_prompt.as_ref() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        let czar_id = self.czar_id();
+        let mut choices = Vec::with_capacity(self.bots.len());
+        for (id, bot) in self.bots.iter_mut() {
+            if Some(*id) == czar_id {
+                continue;
+            }
+
+            if let Some(player) = self.players.get(id) {
+                choices.push((
+                    *id,
+                    bot.select_responses(&mut self.rng, prompt, &player.hand),
+                ));
+            }
+        }
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        for (id, cards) in choices {
+            let responses = cards
+                .into_iter()
+                .map(|card| ResponseData::new(card, self.response_text(card)))
+                .collect::<Vec<_>>();
+            let player = self.players.get_mut(&id).unwrap();
+            player
+                .hand
+                .retain(|card| !responses.iter().any(|response| response.id == *card));
+            player.selections = responses.clone();
+            self.event_log.record(GameEvent::ResponsesSubmitted {
+                player: id,
+                responses,
+            });
+
+            self.broadcast_to_players(
+                &mut client_handler,
+                &ClientBoundPacket::PlayerFinishedPicking(id),
+            )
+            .await;
+        }
+        drop(client_handler);
+
+        self.check_all_selected(network_handler).await;
+    }
+
+    /// Moves on to judging once every player has picked their responses
+    async fn check_all_selected(&mut self, network_handler: &mut NetworkHandler) {
+        let pick_num = match self.current_prompt.as_ref() {
+            Some(prompt) => prompt.pick as usize,
+            None => return,
+        };
+
+        // Bots always pick as soon as the round starts, so they're never waited on
+        let czar_id = self.czar_id();
+        if self.players.iter().all(|(id, player)| {
+            Some(*id) == czar_id
+                || player.is_bot
+                || player.afk
+                || player.selections.len() == pick_num
+        }) {
+            let display_responses = self.display_responses();
+
+            self.broadcast_to_players(
+                &mut network_handler.client_handler.lock().await,
+                &display_responses,
+            )
+            .await;
+
+            match self.judging_mode {
+                JudgingMode::Czar => self.state = GameState::Playing(PlayingState::CzarSelection),
+                JudgingMode::Democracy => {
+                    self.votes.clear();
+                    self.state = GameState::Playing(PlayingState::Voting);
+                    self.bots_vote(network_handler).await;
+                }
+            }
+        }
+    }
+
+    /// The players whose responses can be voted for
+    fn vote_candidates(&self) -> Vec<Uuid> {
+        let pick_num = self
+            .current_prompt
+            .as_ref()
+            .map(|prompt| prompt.pick as usize)
+            .unwrap_or(1);
+        self.players
+            .iter()
+            .filter(|(_, player)| player.selections.len() == pick_num)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    async fn bots_vote(&mut self, network_handler: &mut NetworkHandler) {
+        let candidates = self.vote_candidates();
+        let mut choices = Vec::with_capacity(self.bots.len());
+        for (id, bot) in self.bots.iter_mut() {
+            // Nobody can vote for themselves
+            let options = candidates
+                .iter()
+                .copied()
+                .filter(|candidate| candidate != id)
+                .collect::<Vec<_>>();
+
+            if let Some(choice) = bot.vote(&mut self.rng, &options) {
+                choices.push((*id, choice));
+            }
+        }
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        for (id, choice) in choices {
+            self.votes.insert(id, choice);
+            self.broadcast_to_players(&mut client_handler, &ClientBoundPacket::PlayerVoted(id))
+                .await;
+        }
+        drop(client_handler);
+
+        self.check_all_voted(network_handler).await;
+    }
+
+    /// Tallies the votes once every player who has someone to vote for has voted
+    async fn check_all_voted(&mut self, network_handler: &mut NetworkHandler) {
+        // Bots, AFK players and players yet to rejoin don't hold up the vote
+        if !self.waiting_on(PlayingState::Voting).is_empty() {
+            return;
+        }
+
+        let candidates = self.vote_candidates();
+
+        let mut votes = HashMap::new();
+        for candidate in self.votes.values() {
+            if candidates.contains(candidate) {
+                *votes.entry(*candidate).or_insert(0) += 1;
+            }
+        }
+
+        let most_votes = votes.values().copied().max().unwrap_or(0);
+        let mut winners = votes
+            .iter()
+            .filter(|(_, &count)| count == most_votes)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        break_tie(self.vote_tie_break, &mut winners, &mut self.rng);
+
+        self.award_points(&winners);
+        let end_game = self.is_game_over();
+        self.broadcast_to_players(
+            &mut network_handler.client_handler.lock().await,
+            &ClientBoundPacket::VoteResults {
+                votes,
+                winners,
+                end_game,
+            },
+        )
+        .await;
+
+        self.finish_round(network_handler, end_game).await;
+    }
+
+    /// Gives each winner of the round a point
+    fn award_points(&mut self, winners: &[Uuid]) {
+        self.record_round(winners);
+        self.round_winners = winners.to_vec();
+        self.event_log.record(GameEvent::RoundWon(winners.to_vec()));
+        for winner_id in winners {
+            if let Some(winner) = self.players.get_mut(winner_id) {
+                winner.points += 1;
+            }
+
+            if self.save_write_ins {
+                self.save_winning_write_ins(*winner_id);
+            }
+        }
+    }
+
+    /// Adds the round that was just won to the game's history
+    fn record_round(&mut self, winners: &[Uuid]) {
+        let prompt = match self.current_prompt.clone() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        let mut votes = HashMap::new();
+        if self.judging_mode == JudgingMode::Democracy {
+            for candidate in self.votes.values() {
+                *votes.entry(*candidate).or_insert(0) += 1;
+            }
+        }
+
+        let submissions = self
+            .players
+            .iter()
+            .filter(|(_, player)| !player.selections.is_empty())
+            .map(|(id, player)| Submission {
+                player: *id,
+                responses: player.selections.clone(),
+                pick_time_ms: player.pick_time,
+                votes: votes.get(id).copied().unwrap_or(0),
+                reactions: self.reactions.get(id).copied().unwrap_or(0),
+            })
+            .collect();
+
+        self.history.push(RoundRecord {
+            round: self.round,
+            czar: self.czar_id(),
+            prompt,
+            submissions,
+            winners: winners.to_vec(),
+        });
+    }
+
+    /// Whether anyone else in the game already goes by the name, ignoring case
+    fn name_taken(&self, client_id: Uuid, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.players
+            .iter()
+            .chain(self.queued.iter())
+            .any(|(id, player)| *id != client_id && player.name.to_lowercase() == name)
+    }
+
+    /// Makes a name unique within the game by numbering it if someone else has it, like "Sam (2)"
+    fn unique_name(&self, client_id: Uuid, name: &str) -> String {
+        if !self.name_taken(client_id, name) {
+            return name.to_owned();
+        }
+
+        (2 ..)
+            .map(|number| {
+                let suffix = format!(" ({})", number);
+                // Shorten the name to make room for the number
+                let base = name
+                    .chars()
+                    .take(MAX_NAME_LENGTH - suffix.len())
+                    .collect::<String>();
+                format!("{}{}", base.trim_end(), suffix)
+            })
+            .find(|candidate| !self.name_taken(client_id, candidate))
+            .unwrap()
+    }
+
+    /// A name for players who haven't picked their own yet, like "Player 3"
+    fn default_name(&self, client_id: Uuid) -> String {
+        (1 ..)
+            .map(|number| format!("Player {}", number))
+            .find(|name| !self.name_taken(client_id, name))
+            .unwrap()
+    }
+
+    /// Counts a reaction towards the submission it's aimed at, returning why it was refused if it
+    /// can't be
+    fn react(&mut self, sender_id: Uuid, target: ReactionTarget) -> Result<(), String> {
+        if !self.players.contains_key(&sender_id)
+            && !self.queued.contains_key(&sender_id)
+            && !self.spectators.contains(&sender_id)
+        {
+            return Err("You aren't in this game".to_owned());
+        }
+
+        let player = match target {
+            ReactionTarget::Submission(player) | ReactionTarget::Winner(player) => player,
+        };
+        if player == sender_id {
+            return Err("You can't react to your own cards".to_owned());
+        }
+
+        match target {
+            ReactionTarget::Submission(_) => {
+                let judging = matches!(
+                    self.state,
+                    GameState::Playing(PlayingState::CzarSelection)
+                        | GameState::Playing(PlayingState::Voting)
+                );
+                let submitted = self
+                    .players
+                    .get(&player)
+                    .is_some_and(|player| !player.selections.is_empty());
+                if !judging || !submitted {
+                    return Err("That submission isn't being judged".to_owned());
+                }
+            }
+            ReactionTarget::Winner(_) => {
+                let won = self
+                    .history
+                    .last()
+                    .is_some_and(|round| round.winners.contains(&player));
+                if !won {
+                    return Err("That player didn't win the last round".to_owned());
+                }
+            }
+        }
+
+        if !self.reaction_limiter.allow(sender_id) {
+            return Err("You're reacting too quickly".to_owned());
+        }
+
+        match target {
+            ReactionTarget::Submission(_) => *self.reactions.entry(player).or_insert(0) += 1,
+            ReactionTarget::Winner(_) => {
+                if let Some(submission) = self
+                    .history
+                    .last_mut()
+                    .and_then(|round| round.submissions.iter_mut().find(|s| s.player == player))
+                {
+                    submission.reactions += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn summary(&self) -> GameSummary {
+        let mut standings = self
+            .players
+            .iter()
+            .map(|(id, player)| PlayerStats {
+                id: *id,
+                name: player.name.clone(),
+                points: player.points,
+                rounds_won: self
+                    .history
+                    .iter()
+                    .filter(|round| round.winners.contains(id))
+                    .count() as u32,
+                fastest_pick_ms: self
+                    .history
+                    .iter()
+                    .flat_map(|round| round.submissions.iter())
+                    .filter(|submission| &submission.player == id)
+                    .filter_map(|submission| submission.pick_time_ms)
+                    .min(),
+            })
+            .collect::<Vec<_>>();
+        standings.sort_by_key(|stats| Reverse(stats.points));
+
+        let mut best_combos = self
+            .history
+            .iter()
+            .flat_map(|round| {
+                round
+                    .submissions
+                    .iter()
+                    .filter(move |submission| round.winners.contains(&submission.player))
+                    .map(move |submission| Combo {
+                        prompt: round.prompt.clone(),
+                        submission: submission.clone(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        // Every winning combo is shown, so rounds nobody voted on or reacted to still make the
+        // list, after the rest and in the order they were played
+        best_combos
+            .sort_by_key(|combo| Reverse((combo.submission.votes, combo.submission.reactions)));
+
+        // Ties go to whichever combo was played first
+        let crowd_favorite = self
+            .history
+            .iter()
+            .flat_map(|round| {
+                round.submissions.iter().map(move |submission| Combo {
+                    prompt: round.prompt.clone(),
+                    submission: submission.clone(),
+                })
+            })
+            .filter(|combo| combo.submission.reactions > 0)
+            .min_by_key(|combo| Reverse(combo.submission.reactions));
+
+        GameSummary {
+            standings,
+            best_combos,
+            crowd_favorite,
+        }
+    }
+
+    /// Whether the end condition has been met, checked once a round's winner has been decided
+    fn is_game_over(&self) -> bool {
+        match self.end_condition {
+            EndCondition::Points => self
+                .players
+                .values()
+                .any(|player| player.points >= self.points_to_win),
+            // Cancelled and skipped rounds never make it into the history, so they don't count
+            EndCondition::Rounds(rounds) => self.history.len() >= rounds as usize,
+            EndCondition::TimeLimit(minutes) => self
+                .started_at
+                .map(|start| start.elapsed().as_secs() >= minutes as u64 * 60)
+                .unwrap_or(false),
+            EndCondition::DeckExhausted => self.prompts.is_exhausted(),
+        }
+    }
+
+    /// The players with the most points, after applying the end of game tie break
+    fn game_winners(&mut self) -> Vec<Uuid> {
+        let most_points = self
+            .players
+            .values()
+            .map(|player| player.points)
+            .max()
+            .unwrap_or(0);
+        let mut winners = self
+            .players
+            .iter()
+            .filter(|(_, player)| player.points == most_points)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        break_tie(self.end_tie_break, &mut winners, &mut self.rng);
+        winners
+    }
+
+    async fn finish_round(&mut self, network_handler: &mut NetworkHandler, end_game: bool) {
+        if let Some(record) = self.history.last() {
+            self.broadcast_to_players(
+                &mut network_handler.client_handler.lock().await,
+                &ClientBoundPacket::RoundRecorded(record.clone()),
+            )
+            .await;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+
+        if end_game {
+            self.state = GameState::End;
+            let winners = self.game_winners();
+            self.event_log.record(GameEvent::GameEnded(winners.clone()));
+            self.save_event_log();
+            let mut client_handler = network_handler.client_handler.lock().await;
+            self.broadcast_to_players(
+                &mut client_handler,
+                &ClientBoundPacket::GameWinners(winners),
+            )
+            .await;
+            self.broadcast_to_players(
+                &mut client_handler,
+                &ClientBoundPacket::GameSummary(self.summary()),
+            )
+            .await;
+        } else {
+            self.next_round(network_handler).await;
+        }
+    }
+
+    /// Throws out the current round and starts a new one
+    async fn cancel_round(&mut self, network_handler: &mut NetworkHandler) {
+        self.event_log.record(GameEvent::RoundCancelled);
+        self.broadcast_to_players(
+            &mut network_handler.client_handler.lock().await,
+            &ClientBoundPacket::CancelRound,
+        )
+        .await;
+        self.current_prompt = None;
+        self.next_round(network_handler).await;
+    }
+
+    // This is boxed since bots can finish a round on their own, which starts the next round
+    fn next_round<'a>(
+        &'a mut self,
+        network_handler: &'a mut NetworkHandler,
+    ) -> LocalBoxFuture<'a, ()> {
+        async move {
+            self.state = GameState::Playing(PlayingState::PlayerSelection);
+
+            for index in 0 .. self.players.len() {
+                let player = &mut self.players[index].1;
+                player.pick_time = None;
+                for selection in std::mem::take(&mut player.selections) {
+                    self.responses.discard(selection.id);
+                }
+            }
+
+            // Everyone who could judge is AFK, so hold off on the round until someone is back
+            if !self.rotate_czar() && matches!(self.judging_mode, JudgingMode::Czar) {
+                self.current_prompt = None;
+                self.set_paused(network_handler, true).await;
+                return;
+            }
+            self.round += 1;
+            self.skip_votes.clear();
+            self.reactions.clear();
+            self.prompt_redrawn = false;
+
+            let prompt = self.select_prompt();
+
+            let mut client_handler = network_handler.client_handler.lock().await;
+            let queued = std::mem::replace(&mut self.queued, VecMap::new());
+            for (_, player) in queued {
+                self.seat_player(&mut client_handler, player).await;
+            }
+
+            // Spectators see the prompt but have no hand
+            for id in self.watchers() {
+                client_handler
+                    .send_packet(id, &ClientBoundPacket::NextRound {
+                        czar: self.czar_id(),
+                        prompt: prompt.clone(),
+                        new_responses: Vec::new(),
+                    })
+                    .await;
+            }
+
+            for index in 0 .. self.players.len() {
+                let id = self.players[index].0;
+                let count = self.cards_to_deal(id, &prompt);
+                let round_data = ClientBoundPacket::NextRound {
+                    czar: self.czar_id(),
+                    prompt: prompt.clone(),
+                    new_responses: self.deal(id, count),
+                };
+
+                client_handler.send_packet(id, &round_data).await;
+            }
+            drop(client_handler);
+
+            self.round_started_at = Some(Instant::now());
+            self.event_log.record(GameEvent::RoundStarted {
+                round: self.round,
+                czar: self.czar_id(),
+                prompt: prompt.clone(),
+            });
+            self.current_prompt = Some(prompt);
+            self.play_bots(network_handler).await;
+        }
+        .boxed_local()
+    }
+
+    /// Sends a packet to every player, along with anyone spectating
+    async fn broadcast_to_players<'a>(
+        &self,
+        client_handler: &mut MutexGuard<'a, ClientHandler>,
+        packet: &ClientBoundPacket,
+    ) {
+        client_handler
+            .broadcast(
+                &packet,
+                |client| {
+                    self.players.contains_key(&client.id)
+                        || self.spectators.contains(&client.id)
+                        || self.queued.contains_key(&client.id)
+                },
+                |_| {},
+            )
+            .await;
+    }
+
+    /// Everyone watching the game without playing in it
+    fn watchers(&self) -> Vec<Uuid> {
+        self.spectators
+            .iter()
+            .chain(self.queued.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Whether the game shows up in the server list
+    pub fn is_listed(&self) -> bool {
+        !self.unlisted
+    }
+
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
+
+    /// Whether a new client can join right now with the given password, or the reason they can't
+    pub fn can_join(&self, password: Option<&str>) -> Result<(), String> {
+        if let Some(expected) = self.password.as_ref() {
+            match password {
+                Some(password) if expected.matches(password) => {}
+                Some(_) => return Err("Wrong password".to_owned()),
+                None => return Err(PASSWORD_REQUIRED.to_owned()),
+            }
+        }
+
+        if let GameState::Playing(_) = self.state {
+            match self.late_join {
+                LateJoin::Block =>
+                    return Err("This game can't be joined until it's over".to_owned()),
+                // Spectators don't take up a seat
+                LateJoin::Spectate => return Ok(()),
+                LateJoin::Allow | LateJoin::NextRound => {}
+            }
+        }
+
+        match self.max_players {
+            Some(max_players) if self.players.len() + self.queued.len() >= max_players =>
+                Err("The game is full".to_owned()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a player joining now would get a seat, either straight away or next round, rather
+    /// than only being able to watch
+    pub fn seats_newcomers(&self) -> bool {
+        match self.state {
+            GameState::Playing(_) =>
+                matches!(self.late_join, LateJoin::Allow | LateJoin::NextRound),
+            GameState::WaitingToStart => true,
+            GameState::End => false,
+        }
+    }
+
+    /// The points a player joining now starts with
+    fn starting_points(&self) -> u32 {
+        match self.state {
+            GameState::Playing(_) if self.late_join_handicap => self
+                .players
+                .values()
+                .map(|player| player.points)
+                .min()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    /// Adds a player to the game and tells everyone about them
+    async fn seat_player<'a>(
+        &mut self,
+        client_handler: &mut MutexGuard<'a, ClientHandler>,
+        mut player: Player,
+    ) {
+        let id = player.client_id;
+        player.points = self.starting_points();
+
+        self.event_log.record(GameEvent::PlayerJoined {
+            id,
+            name: player.name.clone(),
+            is_bot: false,
+        });
+        if player.points > 0 {
+            self.event_log.record(GameEvent::PointsChanged {
+                id,
+                points: player.points,
+            });
+        }
+
+        let packet = player.as_packet();
+        self.players.insert(id, player);
+        self.broadcast_to_players(client_handler, &packet).await;
+    }
+
+    /// Lets a late joiner watch the game, dealing them in at the next round if the game queues
+    /// late joiners
+    async fn add_spectator(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
+        let joining_next_round = self.late_join == LateJoin::NextRound;
+        if joining_next_round {
+            let name = self.default_name(client_id);
+            self.queued
+                .insert(client_id, Player::new(client_id, name, false));
+        } else {
+            self.spectators.insert(client_id);
+        }
+
+        let mut packets = self.catch_up_packets(client_id, Vec::new());
+        packets.push(ClientBoundPacket::Spectating { joining_next_round });
+        network_handler
+            .client_handler
+            .lock()
+            .await
+            .send_packets(client_id, &packets)
+            .await;
+    }
+
+    fn settings_as_packets(&self) -> Vec<ClientBoundPacket> {
+        let mut packets = Vec::with_capacity(2 + self.packs.len());
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::MaxPlayers(
+            self.max_players,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(
+            GameSetting::MaxSelectionTime(self.max_selection_time),
+        ));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::PointsToWin(
+            self.points_to_win,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::BlankCards(
+            self.blank_cards,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::SaveWriteIns(
+            self.save_write_ins,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(
+            GameSetting::RebootingTheUniverse(self.rebooting_the_universe),
+        ));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::PackingHeat(
+            self.packing_heat,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::JudgingMode(
+            self.judging_mode,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::HandSize(
+            self.hand_size,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::EndCondition(
+            self.end_condition,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::EndTieBreak(
+            self.end_tie_break,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::CzarRotation(
+            self.czar_rotation,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::VoteTieBreak(
+            self.vote_tie_break,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::PauseTimeout(
+            self.pause_timeout,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(
+            GameSetting::SkipVoteThreshold(self.skip_vote_threshold),
+        ));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::AfkThreshold(
+            self.afk_threshold,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::KickAfk(
+            self.kick_afk,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::LateJoin(
+            self.late_join,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(
+            GameSetting::LateJoinHandicap(self.late_join_handicap),
+        ));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::Unlisted(
+            self.unlisted,
+        )));
+        packets.push(ClientBoundPacket::SettingUpdate(GameSetting::Title(
+            self.title.clone(),
+        )));
+        for pack in self.packs.iter() {
+            packets.push(ClientBoundPacket::SettingUpdate(GameSetting::AddPack(
+                pack.name.clone(),
+            )));
+        }
+        packets
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// The name shown in the server list, falling back to the host's name if there's no title
+    pub fn title(&self) -> String {
+        if self.title.is_empty() {
+            format!("{}'s game", self.host_name())
+        } else {
+            self.title.clone()
+        }
+    }
+
+    pub fn server_entry(&self) -> ServerListEntry {
+        ServerListEntry {
+            id: self.id,
+            title: self.title(),
+            host_name: self.host_name(),
+            players: self.num_players(),
+            max_players: self.max_players,
+            spectators: self.spectators.len() + self.queued.len(),
+            phase: match self.state {
+                GameState::WaitingToStart => GamePhase::Waiting,
+                GameState::Playing(_) => GamePhase::Playing,
+                GameState::End => GamePhase::Ended,
+            },
+            round: self.round,
+            points_to_win: self.points_to_win,
+            packs: self.packs.iter().map(|pack| pack.name.clone()).collect(),
+            has_password: self.has_password(),
+        }
+    }
+}
+
+/// Moves a timer forward by however long it was frozen for during a pause
+fn unfreeze(timer: &mut Option<Instant>, paused_at: Instant) {
+    if let Some(start) = timer {
+        // Timers started during the pause were frozen from the moment they started
+        let frozen = (*start).max(paused_at).elapsed();
+        *start += frozen;
+    }
+}
+
+/// Narrows down a list of tied players according to the given rule
+fn break_tie(tie_break: TieBreak, winners: &mut Vec<Uuid>, rng: &mut ChaCha12Rng) {
+    if winners.len() <= 1 {
+        return;
+    }
+
+    match tie_break {
+        TieBreak::Random => {
+            // Sort first since the order of the winners might not be deterministic
+            winners.sort();
+            let winner = *winners.choose(rng).unwrap();
+            *winners = vec![winner];
+        }
+        TieBreak::AllWin => {}
+        TieBreak::NoWinner => winners.clear(),
+    }
+}
+
+#[async_trait(?Send)]
+impl Listener for Game {
+    async fn client_connected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
+        if let Some(player_id) = self.pending_rejoins.remove(&client_id) {
+            self.reattach(network_handler, player_id, client_id).await;
+            return;
+        }
+
+        if let GameState::Playing(_) = self.state {
+            match self.late_join {
+                LateJoin::NextRound | LateJoin::Spectate => {
+                    self.add_spectator(network_handler, client_id).await;
+                    return;
+                }
+                // Blocked clients are turned away by the lobby before they get here
+                LateJoin::Allow | LateJoin::Block => {}
+            }
+        }
+
+        let set_host = self.players.is_empty();
+        if set_host {
+            self.host_id = client_id;
+        }
+        let player = Player::new(client_id, self.default_name(client_id), set_host);
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        self.seat_player(&mut client_handler, player).await;
+
+        let hand = match (self.state, self.current_prompt.clone()) {
+            (GameState::Playing(_), Some(prompt)) => {
+                let count = self.cards_to_deal(client_id, &prompt);
+                self.deal(client_id, count)
+            }
+            _ => Vec::new(),
+        };
+        let packets = self.catch_up_packets(client_id, hand);
+
+        client_handler.send_packets(client_id, &packets).await;
+    }
+
+    async fn client_disconnected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
+        // Spectators can come and go without affecting the game
+        if self.spectators.remove(&client_id) || self.queued.remove(&client_id).is_some() {
+            self.chat.forget(client_id);
+            self.reaction_limiter.forget(client_id);
+            return;
+        }
+
+        // The player might already be gone, like an AFK player who was sent back to the lobby
+        let index = match self.players.keys().position(|&id| id == client_id) {
+            Some(index) => index,
+            None => return,
+        };
+
+        // If the final human player is leaving
+        if self
+            .players
+            .iter()
+            .all(|(id, player)| *id == client_id || player.is_bot)
+        {
+            // Games that never started aren't worth keeping
+            if !matches!(self.state, GameState::WaitingToStart) {
+                self.event_log.record(GameEvent::PlayerLeft(client_id));
+                self.save_event_log();
+            }
+
+            // Send anyone still watching back to the lobby since the game is over
+            let mut client_handler = network_handler.client_handler.lock().await;
+            let watchers = self.watchers();
+            for &id in watchers.iter() {
+                client_handler
+                    .send_packet(
+                        id,
+                        &ClientBoundPacket::Kicked("Everyone left the game".to_owned()),
+                    )
+                    .await;
+            }
+            drop(client_handler);
+            self.spectators.clear();
+            self.queued = VecMap::new();
+            for id in watchers {
+                network_handler
+                    .forward_client(id, *LOBBY_ID.get().unwrap())
+                    .await;
+            }
+
+            self.players = VecMap::new();
+            self.awaiting_rejoin.clear();
+            self.pending_rejoins.clear();
+            self.bots.clear();
+            self.state = GameState::End;
+            return;
+        }
+
+        // Cancel the round if the czar left
+        let skip_round = Some(client_id) == self.czar_id();
+
+        let player = self.players.remove(&client_id).unwrap();
+        self.awaiting_rejoin.remove(&client_id);
+        self.pending_rejoins
+            .retain(|_, player_id| *player_id != client_id);
+        self.event_log.record(GameEvent::PlayerLeft(client_id));
+        self.skip_votes.remove(&client_id);
+        self.chat.forget(client_id);
+        self.reaction_limiter.forget(client_id);
+
+        for &card in player.hand.iter() {
+            self.responses.discard(card);
+        }
+        for selection in player.selections.iter() {
+            self.responses.discard(selection.id);
+        }
+
+        // Keep the czar index on the same player. If the czar left, move it back to the player
+        // before them so whoever took their seat is next in line rather than skipped.
+        if self.czar_index >= index {
+            self.czar_index = (self.czar_index + self.players.len() - 1) % self.players.len();
+        }
+
+        let new_host = if player.is_host {
+            self.players
+                .iter_mut()
+                .find(|(_, player)| !player.is_bot)
+                .map(|(id, player)| {
+                    player.is_host = true;
+                    id
+                })
+                .copied()
+        } else {
+            None
+        };
+
+        if let Some(host_id) = new_host {
+            self.host_id = host_id;
+        }
+
+        let packet = ClientBoundPacket::RemovePlayer {
+            id: player.client_id,
+            new_host,
+        };
+
+        let mut client_handler = network_handler.client_handler.lock().await;
+        self.broadcast_to_players(&mut client_handler, &packet)
+            .await;
+
+        drop(client_handler);
+        self.votes.remove(&client_id);
+
+        // A paused round is left as it is until the game resumes
+        if self.paused_at.is_some() {
+            return;
+        }
+
+        if skip_round {
+            self.cancel_round(network_handler).await;
+            return;
+        }
+
+        // The round might have only been waiting on the player who left
+        match self.state {
+            GameState::Playing(PlayingState::PlayerSelection) =>
+                self.check_all_selected(network_handler).await,
+            GameState::Playing(PlayingState::Voting) => self.check_all_voted(network_handler).await,
+            _ => {}
         }
     }
 
@@ -349,11 +2200,86 @@ impl Listener for Game {
         packet: &ServerBoundPacket,
         sender_id: Uuid,
     ) -> PacketResponse {
+        self.mark_active(network_handler, sender_id).await;
+
         // Packets we want to respond to no matter what state we're in
         match packet {
+            ServerBoundPacket::RequestHistory => {
+                network_handler
+                    .client_handler
+                    .lock()
+                    .await
+                    .send_packet(
+                        sender_id,
+                        &ClientBoundPacket::RoundHistory(self.history.clone()),
+                    )
+                    .await;
+                return PacketResponse::Accepted;
+            }
+            ServerBoundPacket::SendChat(text) => {
+                let name = match (self.players.get(&sender_id), self.queued.get(&sender_id)) {
+                    (Some(player), _) | (None, Some(player)) => player.name.clone(),
+                    _ if self.spectators.contains(&sender_id) => "Spectator".to_owned(),
+                    _ => return PacketResponse::Rejected,
+                };
+
+                let message = match self.chat.post(sender_id, &name, text) {
+                    Ok(message) => message,
+                    Err(reason) => return PacketResponse::RejectedWithReason(reason),
+                };
+
+                self.broadcast_to_players(
+                    &mut network_handler.client_handler.lock().await,
+                    &ClientBoundPacket::Chat {
+                        scope: ChatScope::Game,
+                        message,
+                    },
+                )
+                .await;
+                return PacketResponse::Accepted;
+            }
+            &ServerBoundPacket::MuteChat { id, muted } => {
+                if self.host_id != sender_id {
+                    return PacketResponse::RejectedWithReason(
+                        "Only the host can mute players".to_owned(),
+                    );
+                }
+                if id == sender_id || !self.players.contains_key(&id) {
+                    return PacketResponse::Rejected;
+                }
+
+                self.chat.set_muted(id, muted);
+                self.broadcast_to_players(
+                    &mut network_handler.client_handler.lock().await,
+                    &ClientBoundPacket::ChatMuted { id, muted },
+                )
+                .await;
+                return PacketResponse::Accepted;
+            }
+            &ServerBoundPacket::React { target, reaction } => {
+                if let Err(reason) = self.react(sender_id, target) {
+                    return PacketResponse::RejectedWithReason(reason);
+                }
+
+                self.broadcast_to_players(
+                    &mut network_handler.client_handler.lock().await,
+                    &ClientBoundPacket::Reacted { target, reaction },
+                )
+                .await;
+                return PacketResponse::Accepted;
+            }
             ServerBoundPacket::SetPlayerName(name) => {
+                let name = match sanitize_name(name) {
+                    Ok(name) => self.unique_name(sender_id, &name),
+                    Err(reason) => return PacketResponse::RejectedWithReason(reason),
+                };
+
                 if let Some(player) = self.players.get_mut(&sender_id) {
                     player.name = name.clone();
+                    self.event_log.record(GameEvent::PlayerRenamed {
+                        id: sender_id,
+                        name: name.clone(),
+                    });
                     self.broadcast_to_players(
                         &mut network_handler.client_handler.lock().await,
                         &ClientBoundPacket::UpdatePlayerName {
@@ -363,112 +2289,246 @@ impl Listener for Game {
                     )
                     .await;
                     return PacketResponse::Accepted;
+                } else if let Some(player) = self.queued.get_mut(&sender_id) {
+                    // Everyone else finds out their name once they're dealt in
+                    player.name = name.clone();
+                    network_handler
+                        .client_handler
+                        .lock()
+                        .await
+                        .send_packet(sender_id, &ClientBoundPacket::UpdatePlayerName {
+                            id: sender_id,
+                            name,
+                        })
+                        .await;
+                    return PacketResponse::Accepted;
                 } else {
                     return PacketResponse::Rejected;
                 }
             }
+            &ServerBoundPacket::SetPaused(paused) => {
+                if self.host_id != sender_id {
+                    return PacketResponse::Rejected;
+                }
+
+                if let GameState::Playing(_) = self.state {
+                    self.set_paused(network_handler, paused).await;
+                    return PacketResponse::Accepted;
+                } else {
+                    return PacketResponse::RejectedWithReason(
+                        "Only games in progress can be paused".to_owned(),
+                    );
+                }
+            }
             _ => {}
         }
 
+        if let (GameState::Playing(_), Some(_)) = (self.state, self.paused_at) {
+            return PacketResponse::RejectedWithReason("The game is paused".to_owned());
+        }
+
         match self.state {
-            GameState::WaitingToStart => {
-                match packet {
-                    ServerBoundPacket::StartGame => {
-                        if self.host_id != sender_id {
-                            return PacketResponse::Rejected;
-                        }
+            GameState::WaitingToStart => match packet {
+                ServerBoundPacket::StartGame => {
+                    if self.host_id != sender_id {
+                        return PacketResponse::Rejected;
+                    }
 
-                        // TODO: Initialize round
-                        self.initialize_prompts();
-                        self.initialize_responses();
+                    return self.start_game(network_handler).await;
+                }
+
+                ServerBoundPacket::AddBot(kind) => {
+                    if self.host_id != sender_id {
+                        return PacketResponse::Rejected;
+                    }
+
+                    if self.bots.len() >= MAX_BOTS {
+                        return PacketResponse::RejectedWithReason(format!(
+                            "Games cannot have more than {} bots",
+                            MAX_BOTS
+                        ));
+                    }
 
-                        // This branch should never be taken
-                        if self.available_responses.is_empty() || self.available_prompts.is_empty()
-                        {
+                    if let Some(max_players) = self.max_players {
+                        if self.players.len() >= max_players {
                             return PacketResponse::RejectedWithReason(
-                                "No packs selected".to_owned(),
+                                "The game is full".to_owned(),
                             );
                         }
+                    }
 
-                        // Select the first czar
-                        self.czar_index = thread_rng().gen_range(0 .. self.players.len());
-
-                        // Select the prompt
-                        let prompt = self.select_prompt();
+                    let bot = create_bot(*kind);
+                    let id = Uuid::from_u128(self.rng.gen());
+                    let player = Player::bot(id, self.unique_name(id, &bot.name()));
 
-                        let mut client_handler = network_handler.client_handler.lock().await;
-                        for index in 0 .. self.players.len() {
-                            let mut responses = Vec::with_capacity(10);
-                            self.add_responses(&mut responses, 10);
+                    self.broadcast_to_players(
+                        &mut network_handler.client_handler.lock().await,
+                        &player.as_packet(),
+                    )
+                    .await;
 
-                            let round_data = ClientBoundPacket::NextRound {
-                                czar: self.players[self.czar_index].0,
-                                prompt: prompt.clone(),
-                                new_responses: responses,
-                            };
+                    self.event_log.record(GameEvent::PlayerJoined {
+                        id,
+                        name: player.name.clone(),
+                        is_bot: true,
+                    });
+                    self.players.insert(id, player);
+                    self.bots.insert(id, bot);
+                }
 
-                            client_handler
-                                .send_packets(self.players[index].0, &[
-                                    ClientBoundPacket::StartGame,
-                                    round_data,
-                                ])
-                                .await;
-                        }
+                ServerBoundPacket::RemoveBot(id) => {
+                    if self.host_id != sender_id {
+                        return PacketResponse::Rejected;
+                    }
 
-                        self.current_prompt = Some(prompt);
-                        self.state = GameState::Playing(PlayingState::PlayerSelection);
+                    if self.bots.remove(id).is_none() {
+                        return PacketResponse::RejectedWithReason(format!(
+                            "Invalid bot ID: {}",
+                            id
+                        ));
                     }
 
-                    ServerBoundPacket::UpdateSetting(setting) => {
-                        if self.host_id != sender_id {
-                            return PacketResponse::Rejected;
-                        }
+                    self.players.remove(id);
+                    self.event_log.record(GameEvent::PlayerLeft(*id));
+                    self.broadcast_to_players(
+                        &mut network_handler.client_handler.lock().await,
+                        &ClientBoundPacket::RemovePlayer {
+                            id: *id,
+                            new_host: None,
+                        },
+                    )
+                    .await;
+                }
 
-                        match setting {
-                            &GameSetting::MaxPlayers(limit) => self.max_players = limit,
-                            &GameSetting::MaxSelectionTime(limit) =>
-                                self.max_selection_time = limit,
-                            &GameSetting::PointsToWin(points) => self.points_to_win = points,
-                            GameSetting::AddPack(pack_name) => {
-                                if self.packs.iter().any(|pack| &pack.name == pack_name) {
-                                    return PacketResponse::Rejected;
-                                }
+                ServerBoundPacket::UpdateSetting(setting) => {
+                    if self.host_id != sender_id {
+                        return PacketResponse::Rejected;
+                    }
+
+                    if let Err(reason) = validate_setting(setting) {
+                        return PacketResponse::RejectedWithReason(reason);
+                    }
 
-                                let pack =
-                                    match self.pack_store.write().unwrap().load_pack(pack_name) {
-                                        Ok(pack) => pack,
-                                        Err(e) => {
-                                            let error =
-                                                format!("Failed to load pack {}: {}", pack_name, e);
-                                            error!("{}", error);
-                                            return PacketResponse::RejectedWithReason(error);
-                                        }
-                                    };
-                                self.packs.push(pack);
+                    match setting {
+                        &GameSetting::MaxPlayers(limit) => {
+                            if limit.is_some_and(|limit| limit < self.players.len()) {
+                                return PacketResponse::RejectedWithReason(format!(
+                                    "There are already {} players in the game",
+                                    self.players.len()
+                                ));
                             }
-                            GameSetting::RemovePack(pack_name) => {
-                                self.packs.retain(|pack| &pack.name != pack_name);
+                            self.max_players = limit;
+                        }
+                        &GameSetting::MaxSelectionTime(limit) => {
+                            self.max_selection_time = limit;
+                            // Start any running deadline over so it isn't cut short by a lower limit
+                            self.deadline = None;
+                        }
+                        &GameSetting::PointsToWin(points) => self.points_to_win = points,
+                        &GameSetting::BlankCards(count) => self.blank_cards = count,
+                        &GameSetting::SaveWriteIns(save) => self.save_write_ins = save,
+                        &GameSetting::RebootingTheUniverse(enabled) =>
+                            self.rebooting_the_universe = enabled,
+                        &GameSetting::PackingHeat(enabled) => self.packing_heat = enabled,
+                        &GameSetting::JudgingMode(mode) => self.judging_mode = mode,
+                        &GameSetting::VoteTieBreak(tie_break) => self.vote_tie_break = tie_break,
+                        &GameSetting::HandSize(size) => self.hand_size = size,
+                        &GameSetting::EndCondition(condition) => self.end_condition = condition,
+                        &GameSetting::EndTieBreak(tie_break) => self.end_tie_break = tie_break,
+                        &GameSetting::CzarRotation(rotation) => self.czar_rotation = rotation,
+                        &GameSetting::PauseTimeout(minutes) => self.pause_timeout = minutes,
+                        &GameSetting::SkipVoteThreshold(threshold) =>
+                            self.skip_vote_threshold = threshold,
+                        &GameSetting::AfkThreshold(missed) => self.afk_threshold = missed,
+                        &GameSetting::KickAfk(kick) => self.kick_afk = kick,
+                        &GameSetting::LateJoin(late_join) => self.late_join = late_join,
+                        &GameSetting::LateJoinHandicap(enabled) =>
+                            self.late_join_handicap = enabled,
+                        GameSetting::Password(password) =>
+                            self.password = password.as_deref().map(Password::new),
+                        &GameSetting::Unlisted(unlisted) => self.unlisted = unlisted,
+                        GameSetting::Title(title) => self.title = title.trim().to_owned(),
+                        GameSetting::AddPack(pack_name) => {
+                            if self.packs.iter().any(|pack| &pack.name == pack_name) {
+                                return PacketResponse::Rejected;
                             }
+
+                            let pack = match self.pack_store.write().unwrap().load_pack(pack_name) {
+                                Ok(pack) => pack,
+                                Err(e) => {
+                                    let error = format!("Failed to load pack {}: {}", pack_name, e);
+                                    error!("{}", error);
+                                    return PacketResponse::RejectedWithReason(error);
+                                }
+                            };
+                            self.packs.push(pack);
+                        }
+                        GameSetting::RemovePack(pack_name) => {
+                            self.packs.retain(|pack| &pack.name != pack_name);
                         }
+                    }
 
-                        self.broadcast_to_players(
-                            &mut network_handler.client_handler.lock().await,
-                            &ClientBoundPacket::echo_setting_update(setting),
-                        )
-                        .await;
+                    // The password stays out of the log and is never sent back out
+                    if matches!(setting, GameSetting::Password(_)) {
+                        return PacketResponse::Accepted;
                     }
 
-                    _ => return PacketResponse::Rejected,
+                    self.event_log
+                        .record(GameEvent::SettingChanged(setting.clone()));
+                    self.broadcast_to_players(
+                        &mut network_handler.client_handler.lock().await,
+                        &ClientBoundPacket::echo_setting_update(setting),
+                    )
+                    .await;
                 }
-            }
+
+                _ => return PacketResponse::Rejected,
+            },
             GameState::Playing(PlayingState::PlayerSelection) => {
-                let card = match packet {
-                    &ServerBoundPacket::SelectResponse(card) => card,
+                let response = match packet {
+                    &ServerBoundPacket::SelectResponse(card) => {
+                        if card.is_blank() {
+                            return PacketResponse::RejectedWithReason(
+                                "Blank cards need to be written on".to_owned(),
+                            );
+                        }
+
+                        // Cards are only looked up once we know they're real, so made up ids
+                        // can't index past the end of a pack
+                        let in_hand = self
+                            .players
+                            .get(&sender_id)
+                            .is_some_and(|player| player.hand.contains(&card));
+                        if !in_hand {
+                            return PacketResponse::RejectedWithReason(
+                                "That card is not in your hand".to_owned(),
+                            );
+                        }
+
+                        ResponseData::new(card, self.response_text(card))
+                    }
+                    ServerBoundPacket::SelectWriteIn { card, text } => {
+                        if self.blank_cards == 0 || !card.is_blank() {
+                            return PacketResponse::RejectedWithReason(
+                                "Only blank cards can be written on".to_owned(),
+                            );
+                        }
+
+                        match sanitize(text, MAX_WRITE_IN_LENGTH) {
+                            Ok(text) => ResponseData::new(*card, text),
+                            Err(reason) => return PacketResponse::RejectedWithReason(reason),
+                        }
+                    }
+                    ServerBoundPacket::RebootTheUniverse =>
+                        return self.reboot_the_universe(network_handler, sender_id).await,
+                    ServerBoundPacket::RedrawPrompt =>
+                        return self.redraw_prompt(network_handler, sender_id).await,
+                    ServerBoundPacket::VoteToSkipPrompt =>
+                        return self.vote_to_skip(network_handler, sender_id).await,
                     _ => return PacketResponse::Rejected,
                 };
 
-                let czar_id = self.players[self.czar_index].0;
-                if sender_id == czar_id {
+                if Some(sender_id) == self.czar_id() {
                     return PacketResponse::Rejected;
                 }
 
@@ -477,23 +2537,36 @@ impl Listener for Game {
                     None => return PacketResponse::Rejected,
                 };
 
-                let selections = match self
-                    .players
-                    .get_mut(&sender_id)
-                    .map(|player| &mut player.selections)
-                {
-                    Some(selections) => selections,
+                let player = match self.players.get_mut(&sender_id) {
+                    Some(player) => player,
                     None => return PacketResponse::Rejected,
                 };
 
 
-                if selections.len() >= pick_num {
+                if player.selections.len() >= pick_num {
                     return PacketResponse::Rejected;
                 }
 
-                selections.push(card);
+                let hand_index = match player.hand.iter().position(|&card| card == response.id) {
+                    Some(index) => index,
+                    None =>
+                        return PacketResponse::RejectedWithReason(
+                            "That card is not in your hand".to_owned(),
+                        ),
+                };
 
-                if selections.len() == pick_num {
+                player.hand.remove(hand_index);
+                player.selections.push(response);
+
+                if player.selections.len() == pick_num {
+                    player.pick_time = self
+                        .round_started_at
+                        .map(|start| start.elapsed().as_millis() as u64);
+                    let responses = player.selections.clone();
+                    self.event_log.record(GameEvent::ResponsesSubmitted {
+                        player: sender_id,
+                        responses,
+                    });
                     self.broadcast_to_players(
                         &mut network_handler.client_handler.lock().await,
                         &ClientBoundPacket::PlayerFinishedPicking(sender_id),
@@ -501,21 +2574,7 @@ impl Listener for Game {
                     .await;
                 }
 
-                if self
-                    .players
-                    .iter()
-                    .all(|(id, player)| *id == czar_id || player.selections.len() == pick_num)
-                {
-                    let display_responses = self.display_responses();
-
-                    self.broadcast_to_players(
-                        &mut network_handler.client_handler.lock().await,
-                        &display_responses,
-                    )
-                    .await;
-
-                    self.state = GameState::Playing(PlayingState::CzarSelection);
-                }
+                self.check_all_selected(network_handler).await;
             }
             GameState::Playing(PlayingState::CzarSelection) => {
                 let winner_id = match packet {
@@ -523,22 +2582,19 @@ impl Listener for Game {
                     _ => return PacketResponse::Rejected,
                 };
 
-                let czar_id = self.players[self.czar_index].0;
-                if sender_id != czar_id {
+                if Some(sender_id) != self.czar_id() {
                     return PacketResponse::Rejected;
                 }
 
-                let winner = match self.players.get_mut(&winner_id) {
-                    Some(winner) => winner,
-                    None =>
-                        return PacketResponse::RejectedWithReason(format!(
-                            "Invalid player ID: {}",
-                            winner_id
-                        )),
-                };
+                if !self.players.contains_key(&winner_id) {
+                    return PacketResponse::RejectedWithReason(format!(
+                        "Invalid player ID: {}",
+                        winner_id
+                    ));
+                }
 
-                winner.points += 1;
-                let end_game = winner.points >= self.points_to_win;
+                self.award_points(&[winner_id]);
+                let end_game = self.is_game_over();
                 self.broadcast_to_players(
                     &mut network_handler.client_handler.lock().await,
                     &ClientBoundPacket::DisplayWinner {
@@ -548,79 +2604,89 @@ impl Listener for Game {
                 )
                 .await;
 
-                std::thread::sleep(std::time::Duration::from_millis(1500));
+                self.finish_round(network_handler, end_game).await;
+            }
+            GameState::Playing(PlayingState::Voting) => {
+                let candidate = match packet {
+                    &ServerBoundPacket::VoteForResponse(candidate) => candidate,
+                    _ => return PacketResponse::Rejected,
+                };
+
+                if candidate == sender_id {
+                    return PacketResponse::RejectedWithReason(
+                        "You can't vote for your own responses".to_owned(),
+                    );
+                }
 
-                if end_game {
-                    self.state = GameState::End;
-                } else {
-                    self.next_round(network_handler).await;
+                if !self.players.contains_key(&sender_id) || self.votes.contains_key(&sender_id) {
+                    return PacketResponse::Rejected;
                 }
-            }
-            GameState::End => {
-                match packet {
-                    ServerBoundPacket::LeaveGame => {
-                        network_handler
-                            .forward_client(sender_id, *LOBBY_ID.get().unwrap())
-                            .await
-                            .unwrap();
-                    }
-                    ServerBoundPacket::StartGame => {
-                        if self.host_id != sender_id {
-                            return PacketResponse::Rejected;
-                        }
 
-                        for (_, player) in self.players.iter_mut() {
-                            player.points = 0;
-                            player.selections = Vec::new();
-                        }
+                if !self.vote_candidates().contains(&candidate) {
+                    return PacketResponse::RejectedWithReason(format!(
+                        "Invalid player ID: {}",
+                        candidate
+                    ));
+                }
 
+                self.votes.insert(sender_id, candidate);
+                self.broadcast_to_players(
+                    &mut network_handler.client_handler.lock().await,
+                    &ClientBoundPacket::PlayerVoted(sender_id),
+                )
+                .await;
 
-                        // TODO: Initialize round
-                        self.initialize_prompts();
-                        self.initialize_responses();
+                self.check_all_voted(network_handler).await;
+            }
+            GameState::End => match packet {
+                ServerBoundPacket::LeaveGame => {
+                    network_handler
+                        .forward_client(sender_id, *LOBBY_ID.get().unwrap())
+                        .await
+                        .unwrap();
+                }
+                ServerBoundPacket::StartGame => {
+                    if self.host_id != sender_id {
+                        return PacketResponse::Rejected;
+                    }
 
-                        // This branch should never be taken
-                        if self.available_responses.is_empty() || self.available_prompts.is_empty()
-                        {
-                            return PacketResponse::RejectedWithReason(
-                                "No packs selected".to_owned(),
-                            );
-                        }
+                    return self.start_game(network_handler).await;
+                }
+                _ => {}
+            },
+        }
 
-                        // Select the first czar
-                        self.czar_index = thread_rng().gen_range(0 .. self.players.len());
+        PacketResponse::Accepted
+    }
 
-                        // Select the prompt
-                        let prompt = self.select_prompt();
+    async fn tick(&mut self, network_handler: &mut NetworkHandler) {
+        if let Some(paused_at) = self.paused_at {
+            if paused_at.elapsed() >= self.pause_timeout() {
+                self.set_paused(network_handler, false).await;
+            }
+        }
 
-                        let mut client_handler = network_handler.client_handler.lock().await;
-                        for index in 0 .. self.players.len() {
-                            let mut responses = Vec::with_capacity(10);
-                            self.add_responses(&mut responses, 10);
+        self.check_deadline(network_handler).await;
 
-                            let round_data = ClientBoundPacket::NextRound {
-                                czar: self.players[self.czar_index].0,
-                                prompt: prompt.clone(),
-                                new_responses: responses,
-                            };
+        if let Some(restored_at) = self.restored_at {
+            if restored_at.elapsed() >= REJOIN_GRACE_PERIOD {
+                self.restored_at = None;
 
-                            client_handler
-                                .send_packets(self.players[index].0, &[
-                                    ClientBoundPacket::StartGame,
-                                    round_data,
-                                ])
-                                .await;
-                        }
+                // Anyone who hasn't come back by now is treated as having left
+                let missing = self.awaiting_rejoin.drain().collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    info!(
+                        "Removing {} players who didn't rejoin game {}",
+                        missing.len(),
+                        self.id
+                    );
+                }
 
-                        self.current_prompt = Some(prompt);
-                        self.state = GameState::Playing(PlayingState::PlayerSelection);
-                    }
-                    _ => {}
+                for id in missing {
+                    self.client_disconnected(network_handler, id).await;
                 }
             }
         }
-
-        PacketResponse::Accepted
     }
 
     fn is_terminated(&self) -> bool {
@@ -648,23 +2714,58 @@ impl Drop for Game {
     }
 }
 
-#[derive(Clone)]
-struct Player {
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Player {
     client_id: Uuid,
     name: String,
     is_host: bool,
+    is_bot: bool,
     points: u32,
-    selections: Vec<CardID>,
+    hand: Vec<CardID>,
+    selections: Vec<ResponseData>,
+    // Milliseconds from the start of the round until the player finished picking
+    pick_time: Option<u64>,
+    // Lets the player take back their seat if the game is restored after a restart
+    rejoin_token: Uuid,
+    afk: bool,
+    // Deadlines missed in a row without sending anything
+    missed_deadlines: u32,
+    #[serde(skip)]
+    last_active: Option<Instant>,
 }
 
 impl Player {
-    pub fn new(client_id: Uuid, is_host: bool) -> Self {
+    pub fn new(client_id: Uuid, name: String, is_host: bool) -> Self {
         Player {
             client_id,
-            name: format!("Player #{}", client_id),
+            name,
             is_host,
+            is_bot: false,
+            points: 0,
+            hand: Vec::new(),
+            selections: Vec::new(),
+            pick_time: None,
+            rejoin_token: Uuid::new_v4(),
+            afk: false,
+            missed_deadlines: 0,
+            last_active: Some(Instant::now()),
+        }
+    }
+
+    pub fn bot(id: Uuid, name: String) -> Self {
+        Player {
+            client_id: id,
+            name,
+            is_host: false,
+            is_bot: true,
             points: 0,
+            hand: Vec::new(),
             selections: Vec::new(),
+            pick_time: None,
+            rejoin_token: Uuid::nil(),
+            afk: false,
+            missed_deadlines: 0,
+            last_active: None,
         }
     }
 
@@ -673,20 +2774,393 @@ impl Player {
             id: self.client_id,
             name: self.name.clone(),
             is_host: self.is_host,
+            is_bot: self.is_bot,
             points: self.points,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-enum GameState {
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GameState {
     WaitingToStart,
     Playing(PlayingState),
     End,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
-enum PlayingState {
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PlayingState {
     PlayerSelection,
     CzarSelection,
+    Voting,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{packs::DEFAULT_PACK, settings::default_settings, test_util::*};
+
+    /// Creates a game with the default settings and the given number of players already seated.
+    /// The first player is the host.
+    fn test_game(prompts: usize, responses: usize, players: usize) -> Game {
+        game_with_settings(
+            default_settings(vec![DEFAULT_PACK.to_owned()]),
+            prompts,
+            responses,
+            players,
+        )
+    }
+
+    fn game_with_settings(
+        settings: GameSettings,
+        prompts: usize,
+        responses: usize,
+        players: usize,
+    ) -> Game {
+        let mut game = Game::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            pack_store(prompts, responses),
+            settings,
+        )
+        .unwrap();
+
+        for index in 0 .. players {
+            let id = Uuid::new_v4();
+            if index == 0 {
+                game.host_id = id;
+            }
+            game.players.insert(
+                id,
+                Player::new(id, format!("Player {}", index + 1), index == 0),
+            );
+        }
+
+        game
+    }
+
+    fn hands(game: &Game) -> Vec<Vec<CardID>> {
+        game.players
+            .values()
+            .map(|player| player.hand.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn one_prompt_pack_lasts_multiple_rounds() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(1, 50, 3);
+
+        game.start_game(&mut network_handler).await;
+        game.replace_prompt(&mut network_handler).await;
+        game.next_round(&mut network_handler).await;
+
+        assert_eq!(game.round, 2);
+        assert_eq!(game.current_prompt.as_ref().unwrap().text, "Prompt 0 _");
+    }
+
+    #[tokio::test]
+    async fn same_seed_plays_out_the_same() {
+        let mut network_handler = network_handler();
+        let mut settings = default_settings(vec![DEFAULT_PACK.to_owned()]);
+        settings.seed = Some(1234);
+        let mut first = game_with_settings(settings.clone(), 20, 100, 3);
+        let mut second = game_with_settings(settings, 20, 100, 3);
+
+        first.start_game(&mut network_handler).await;
+        second.start_game(&mut network_handler).await;
+        assert_eq!(first.czar_index, second.czar_index);
+        assert_eq!(first.current_prompt_card, second.current_prompt_card);
+        assert_eq!(hands(&first), hands(&second));
+
+        first.next_round(&mut network_handler).await;
+        second.next_round(&mut network_handler).await;
+        assert_eq!(first.current_prompt_card, second.current_prompt_card);
+        assert_eq!(hands(&first), hands(&second));
+    }
+
+    #[tokio::test]
+    async fn restored_game_keeps_its_random_sequence() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        let mut restored = Game::restore(game.snapshot(), game.pack_store.clone()).unwrap();
+        game.next_round(&mut network_handler).await;
+        restored.next_round(&mut network_handler).await;
+
+        assert_eq!(game.current_prompt_card, restored.current_prompt_card);
+        assert_eq!(hands(&game), hands(&restored));
+    }
+
+    #[tokio::test]
+    async fn snapshot_survives_a_round_trip() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+        let snapshot = serde_json::from_str(&json).unwrap();
+        let restored = Game::restore(snapshot, game.pack_store.clone()).unwrap();
+
+        assert_eq!(restored.round, game.round);
+        assert_eq!(restored.czar_id(), game.czar_id());
+        assert_eq!(restored.current_prompt_card, game.current_prompt_card);
+        assert_eq!(hands(&restored), hands(&game));
+        assert_eq!(restored.awaiting_rejoin.len(), 3);
+    }
+
+    #[test]
+    fn password_is_never_saved_or_sent() {
+        let mut settings = default_settings(vec![DEFAULT_PACK.to_owned()]);
+        settings.password = Some("hunter2".to_owned());
+        let game = game_with_settings(settings, 20, 100, 1);
+
+        assert!(!game.settings_as_packets().iter().any(|packet| matches!(
+            packet,
+            ClientBoundPacket::SettingUpdate(GameSetting::Password(_))
+        )));
+
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+        assert!(!json.contains("hunter2"));
+
+        let snapshot = serde_json::from_str(&json).unwrap();
+        let restored = Game::restore(snapshot, game.pack_store.clone()).unwrap();
+        assert!(restored.has_password());
+        assert!(restored.can_join(Some("hunter2")).is_err());
+        assert!(restored.can_join(None).is_err());
+    }
+
+    #[test]
+    fn taken_names_are_numbered() {
+        let mut game = test_game(20, 100, 2);
+        let first = game.players[0].0;
+        let newcomer = Uuid::new_v4();
+
+        assert_eq!(game.unique_name(first, "Player 1"), "Player 1");
+        assert_eq!(game.unique_name(newcomer, "player 1"), "player 1 (2)");
+
+        game.players.insert(
+            newcomer,
+            Player::new(newcomer, "Player 1 (2)".to_owned(), false),
+        );
+        assert_eq!(game.unique_name(Uuid::new_v4(), "Player 1"), "Player 1 (3)");
+    }
+
+    #[test]
+    fn numbered_names_stay_within_the_limit() {
+        let mut game = test_game(20, 100, 1);
+        let long_name = "x".repeat(MAX_NAME_LENGTH);
+        game.players[0].1.name = long_name.clone();
+
+        let name = game.unique_name(Uuid::new_v4(), &long_name);
+        assert_eq!(name.chars().count(), MAX_NAME_LENGTH);
+        assert!(name.ends_with(" (2)"));
+    }
+
+    #[tokio::test]
+    async fn replaced_prompt_takes_back_extra_cards() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        let czar = game.czar_id();
+        game.current_prompt.as_mut().unwrap().draw = Some(2);
+        for index in 0 .. game.players.len() {
+            let id = game.players[index].0;
+            if Some(id) != czar {
+                game.deal(id, 2);
+            }
+        }
+
+        game.replace_prompt(&mut network_handler).await;
+        assert!(hands(&game).iter().all(|hand| hand.len() == game.hand_size));
+    }
+
+    #[tokio::test]
+    async fn unknown_cards_are_rejected() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        let czar = game.czar_id();
+        let player = game
+            .players
+            .keys()
+            .copied()
+            .find(|&id| Some(id) != czar)
+            .unwrap();
+        for card in [CardID::new(0, 100), CardID::new(7, 0)] {
+            let response = game
+                .handle_packet(
+                    &mut network_handler,
+                    &ServerBoundPacket::SelectResponse(card),
+                    player,
+                )
+                .await;
+            assert!(matches!(response, PacketResponse::RejectedWithReason(_)));
+        }
+        assert!(game.players.get(&player).unwrap().selections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn spectating_isnt_a_seat() {
+        let mut network_handler = network_handler();
+        let mut settings = default_settings(vec![DEFAULT_PACK.to_owned()]);
+        settings.late_join = LateJoin::Spectate;
+        let mut game = game_with_settings(settings, 20, 100, 3);
+        assert!(game.seats_newcomers());
+
+        game.start_game(&mut network_handler).await;
+        assert!(game.can_join(None).is_ok());
+        assert!(!game.seats_newcomers());
+
+        game.late_join = LateJoin::NextRound;
+        assert!(game.seats_newcomers());
+    }
+
+    #[test]
+    fn best_combos_include_every_winner() {
+        let mut game = test_game(20, 100, 2);
+        let (first, second) = (game.players[0].0, game.players[1].0);
+        for round in 1 ..= 8 {
+            let submission = |player| Submission {
+                player,
+                responses: Vec::new(),
+                pick_time_ms: None,
+                votes: 0,
+                reactions: if round == 7 && player == first { 2 } else { 0 },
+            };
+            game.history.push(RoundRecord {
+                round,
+                czar: Some(second),
+                prompt: Prompt {
+                    text: format!("Prompt {} _", round),
+                    pick: 1,
+                    draw: None,
+                },
+                submissions: vec![submission(first), submission(second)],
+                winners: vec![first],
+            });
+        }
+
+        let best_combos = game.summary().best_combos;
+        assert_eq!(best_combos.len(), 8);
+        assert_eq!(best_combos[0].prompt.text, "Prompt 7 _");
+        assert_eq!(best_combos[1].prompt.text, "Prompt 1 _");
+        assert!(best_combos
+            .iter()
+            .all(|combo| combo.submission.player == first));
+    }
+
+    #[tokio::test]
+    async fn unknown_client_disconnecting_is_ignored() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        game.client_disconnected(&mut network_handler, Uuid::new_v4())
+            .await;
+
+        assert_eq!(game.players.len(), 3);
+        assert_eq!(game.round, 1);
+    }
+
+    #[test]
+    fn skip_votes_round_up() {
+        let mut game = test_game(20, 100, 3);
+        game.skip_vote_threshold = Some(50);
+        assert_eq!(game.skip_votes_needed(), Some(2));
+
+        game.skip_vote_threshold = Some(100);
+        assert_eq!(game.skip_votes_needed(), Some(3));
+
+        game.skip_vote_threshold = None;
+        assert_eq!(game.skip_votes_needed(), None);
+    }
+
+    #[test]
+    fn skip_votes_with_no_voters() {
+        let mut game = test_game(20, 100, 0);
+        game.skip_vote_threshold = Some(100);
+        assert_eq!(game.skip_votes_needed(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn players_awaiting_rejoin_arent_waited_on() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        let mut restored = Game::restore(game.snapshot(), game.pack_store.clone()).unwrap();
+        assert!(restored
+            .waiting_on(PlayingState::PlayerSelection)
+            .is_empty());
+
+        let id = restored.players[0].0;
+        restored.client_disconnected(&mut network_handler, id).await;
+        assert!(!restored.awaiting_rejoin.contains(&id));
+    }
+
+    #[tokio::test]
+    async fn vote_doesnt_wait_for_players_awaiting_rejoin() {
+        let mut network_handler = network_handler();
+        let mut settings = default_settings(vec![DEFAULT_PACK.to_owned()]);
+        settings.judging_mode = JudgingMode::Democracy;
+        let mut game = game_with_settings(settings, 20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        for index in 0 .. game.players.len() {
+            let player = &mut game.players[index].1;
+            let card = player.hand.remove(0);
+            player.selections = vec![ResponseData::new(card, Response::new())];
+        }
+        game.state = GameState::Playing(PlayingState::Voting);
+        let (first, second, away) = (game.players[0].0, game.players[1].0, game.players[2].0);
+        game.awaiting_rejoin.insert(away);
+
+        for (voter, candidate) in [(first, second), (second, first)] {
+            game.handle_packet(
+                &mut network_handler,
+                &ServerBoundPacket::VoteForResponse(candidate),
+                voter,
+            )
+            .await;
+        }
+        assert_eq!(game.round, 2);
+    }
+
+    #[tokio::test]
+    async fn round_waits_when_nobody_can_judge() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+
+        for player in game.players.values_mut() {
+            player.afk = true;
+        }
+        game.next_round(&mut network_handler).await;
+        assert!(game.paused_at.is_some());
+        assert_eq!(game.round, 1);
+
+        game.players[0].1.afk = false;
+        game.set_paused(&mut network_handler, false).await;
+        assert_eq!(game.round, 2);
+        assert_eq!(game.czar_index, 0);
+    }
+
+    #[tokio::test]
+    async fn czar_leaving_a_paused_game_waits_for_resume() {
+        let mut network_handler = network_handler();
+        let mut game = test_game(20, 100, 3);
+        game.start_game(&mut network_handler).await;
+        game.set_paused(&mut network_handler, true).await;
+
+        let czar = game.czar_id().unwrap();
+        game.client_disconnected(&mut network_handler, czar).await;
+        assert_eq!(game.round, 1);
+
+        game.set_paused(&mut network_handler, false).await;
+        assert_eq!(game.round, 2);
+        assert_ne!(game.czar_id(), Some(czar));
+    }
 }
@@ -1,26 +1,64 @@
 use std::{
     cell::RefCell,
+    collections::HashMap,
     rc::Rc,
     sync::{Arc, RwLock as StdRwLock},
+    time::{Duration, Instant},
 };
 
-use crate::network::{Listener, NetworkHandler};
+use crate::{
+    network::{
+        client::{Client, ClientHandler},
+        Listener,
+        NetworkHandler,
+    },
+    LOBBY_ID,
+};
 
 use async_trait::async_trait;
-use common::protocol::{
-    clientbound::{ClientBoundPacket, PacketResponse},
-    serverbound::ServerBoundPacket,
+use common::{
+    data::text::sanitize_name,
+    protocol::{
+        clientbound::{ChatScope, ClientBoundPacket, PacketResponse},
+        serverbound::ServerBoundPacket,
+        GamePhase,
+        GameSettings,
+        ServerFilter,
+        ServerListEntry,
+        ServerSort,
+        INVITE_CODE_ALPHABET,
+        INVITE_CODE_LENGTH,
+    },
 };
-use futures::future::join_all;
-use log::warn;
+use log::{error, info, warn};
+use rand::{seq::SliceRandom, thread_rng};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use super::{packs::PackStore, Game};
+use super::{
+    chat::ChatRoom,
+    packs::{PackStore, DEFAULT_PACK},
+    settings::{default_settings, validate_settings},
+    snapshot::{self, GameSnapshot},
+    Game,
+};
+
+/// How often running games are saved to disk
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct Lobby {
     pack_store: Arc<StdRwLock<PackStore>>,
     games: Vec<Rc<RwLock<Game>>>,
+    // Set from the console to make new games reproducible while debugging
+    debug_seed: Option<u64>,
+    last_snapshot: Instant,
+    // What clients in the lobby were last told about each listed game
+    listed: HashMap<Uuid, ServerListEntry>,
+    // How each client last asked for the server list to be sorted and filtered
+    views: HashMap<Uuid, (ServerSort, ServerFilter)>,
+    // The names clients picked while in the lobby, for chat
+    names: HashMap<Uuid, String>,
+    chat: ChatRoom,
 }
 
 impl Lobby {
@@ -28,39 +66,365 @@ impl Lobby {
         Lobby {
             pack_store,
             games: Vec::new(),
+            debug_seed: None,
+            last_snapshot: Instant::now(),
+            listed: HashMap::new(),
+            views: HashMap::new(),
+            names: HashMap::new(),
+            chat: ChatRoom::new(),
         }
     }
-}
 
-#[async_trait(?Send)]
-impl Listener for Lobby {
-    async fn client_connected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
-        // Drop any Games that have terminated
+    fn drop_terminated_games(&mut self) {
         self.games.retain(|g| {
             // Same note as in handler.rs
             // now_or_never could return None if the RwLock is being used but it doesn't matter
             !futures::FutureExt::now_or_never(async { g.read().await.is_terminated() })
                 .unwrap_or(false)
         });
+    }
+
+    /// Tells every client in the lobby about games that were added, changed or removed since the
+    /// last update
+    async fn update_server_list(&mut self, network_handler: &mut NetworkHandler) {
+        self.drop_terminated_games();
 
-        let games_future = self.games.iter().map(|g| g.read()).collect::<Vec<_>>();
-        let games = join_all(games_future).await;
+        let mut current = HashMap::new();
+        let mut any_busy = false;
+        for game in self.games.iter() {
+            match futures::FutureExt::now_or_never(game.read()) {
+                Some(game) =>
+                    if game.is_listed() && !game.is_terminated() {
+                        current.insert(game.id, game.server_entry());
+                    },
+                None => any_busy = true,
+            }
+        }
 
+        // Each changed game along with whether clients could already have it in their list
+        let mut changed = Vec::new();
+        for (id, entry) in current.iter() {
+            match self.listed.get(id) {
+                None => changed.push((entry.clone(), false)),
+                Some(old) if old != entry => changed.push((entry.clone(), true)),
+                _ => {}
+            }
+        }
+
+        // A game we couldn't look at might still be listed, so wait until we can to remove any
+        let mut removed = Vec::new();
+        if any_busy {
+            self.listed.extend(current);
+        } else {
+            removed.extend(self.listed.keys().filter(|id| !current.contains_key(id)));
+            self.listed = current;
+        }
+
+        if changed.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let lobby_id = *LOBBY_ID.get().unwrap();
         let mut client_handler = network_handler.client_handler.lock().await;
+        self.forget_departed_clients(&client_handler);
 
-        match client_handler
-            .send_packet(client_id, &ClientBoundPacket::ServerList {
-                servers: games
-                    .iter()
-                    .map(|g| (g.id, g.host_name(), g.num_players(), g.max_players))
-                    .collect(),
-            })
+        let default_filter = ServerFilter::default();
+        let views = &self.views;
+        let filter_of = |client_id: Uuid| {
+            views
+                .get(&client_id)
+                .map_or(&default_filter, |(_, filter)| filter)
+        };
+        let on_error = |client: &Client| warn!("Error sending server list update to {}", client.id);
+
+        for (entry, was_listed) in changed {
+            let packet = if was_listed {
+                ClientBoundPacket::ServerUpdated(entry.clone())
+            } else {
+                ClientBoundPacket::ServerAdded(entry.clone())
+            };
+            client_handler
+                .broadcast(
+                    &packet,
+                    |client| client.listener == lobby_id && filter_of(client.id).matches(&entry),
+                    on_error,
+                )
+                .await;
+
+            // Games that stopped matching a client's filter drop out of their list
+            if was_listed {
+                client_handler
+                    .broadcast(
+                        &ClientBoundPacket::ServerRemoved(entry.id),
+                        |client| {
+                            client.listener == lobby_id && !filter_of(client.id).matches(&entry)
+                        },
+                        on_error,
+                    )
+                    .await;
+            }
+        }
+
+        for id in removed {
+            client_handler
+                .broadcast(
+                    &ClientBoundPacket::ServerRemoved(id),
+                    |client| client.listener == lobby_id,
+                    on_error,
+                )
+                .await;
+        }
+    }
+
+    /// Drops what the lobby kept about clients who have disconnected from a game since
+    fn forget_departed_clients(&mut self, client_handler: &ClientHandler) {
+        let present = |id: Uuid| client_handler.get_client(id).is_some();
+        self.views.retain(|&id, _| present(id));
+        self.names.retain(|&id, _| present(id));
+        self.chat.retain(present);
+    }
+
+    /// The name a client goes by in lobby chat
+    fn name_of(&self, client_id: Uuid) -> String {
+        self.names
+            .get(&client_id)
+            .cloned()
+            // A few characters of the id tell nameless clients apart without being unreadable
+            .unwrap_or_else(|| format!("Guest {}", &client_id.to_string()[.. 4]))
+    }
+
+    /// Sends a client every game matching their filter, in the order they asked for
+    async fn send_server_list(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
+        self.update_server_list(network_handler).await;
+
+        let (sort, filter) = self.views.get(&client_id).cloned().unwrap_or_default();
+        let mut servers = self
+            .listed
+            .values()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect::<Vec<_>>();
+        servers.sort_by(|a, b| sort.compare(a, b));
+
+        match network_handler
+            .client_handler
+            .lock()
+            .await
+            .send_packet(client_id, &ClientBoundPacket::ServerList { servers })
             .await
         {
             Some(Err(e)) => warn!("Error sending server list to client: {}", e),
             _ => {}
         };
+    }
+
+    fn find_game(&self, game_id: Uuid) -> Option<Rc<RwLock<Game>>> {
+        self.games
+            .iter()
+            .find(|game| {
+                // Same note as in client_connected
+                futures::FutureExt::now_or_never(async { game.read().await.id == game_id })
+                    .unwrap_or(false)
+            })
+            .cloned()
+    }
+
+    fn find_game_by_code(&self, code: &str) -> Option<Rc<RwLock<Game>>> {
+        self.games
+            .iter()
+            .find(|game| {
+                futures::FutureExt::now_or_never(async { game.read().await.code == code })
+                    .unwrap_or(false)
+            })
+            .cloned()
+    }
+
+    /// Makes an invite code that no running game is using
+    fn new_invite_code(&self) -> String {
+        let mut rng = thread_rng();
+        loop {
+            let code = (0 .. INVITE_CODE_LENGTH)
+                .map(|_| *INVITE_CODE_ALPHABET.choose(&mut rng).unwrap() as char)
+                .collect::<String>();
+            if self.find_game_by_code(&code).is_none() {
+                return code;
+            }
+        }
+    }
+
+    /// Makes a new game and sends its host to it
+    async fn create_game(
+        &mut self,
+        network_handler: &mut NetworkHandler,
+        host_id: Uuid,
+        mut settings: GameSettings,
+    ) -> PacketResponse {
+        if let Err(reason) = validate_settings(&settings) {
+            return PacketResponse::RejectedWithReason(reason);
+        }
+
+        if self.debug_seed.is_some() {
+            settings.seed = self.debug_seed;
+        }
+
+        let new_game = match Game::new(
+            // Use a fake Uuid to create the game because we can't know what uuid is assigned to it
+            Uuid::from_u128(0),
+            host_id,
+            self.pack_store.clone(),
+            settings,
+        ) {
+            Ok(g) => Rc::new(RwLock::new(g)),
+            Err(e) => {
+                warn!("Error making new game {}", e);
+                return PacketResponse::RejectedWithReason("Error creating new game".to_owned());
+            }
+        };
+
+        let code = self.new_invite_code();
+        let listener_id = network_handler.add_listener(new_game.clone());
+        let mut game = new_game.write().await;
+        game.id = listener_id;
+        game.code = code;
+        info!(
+            "Created game {} ({}) with seed {}",
+            listener_id,
+            game.code,
+            game.seed()
+        );
+        drop(game);
+        self.games.push(new_game);
+
+        match network_handler.forward_client(host_id, listener_id).await {
+            Some(_) => PacketResponse::Accepted,
+            None => PacketResponse::Rejected,
+        }
+    }
+
+    /// The open public game that suits a player best. Games that haven't started come first, then
+    /// games using more of the player's packs, then games with more room.
+    async fn best_open_game(&self, packs: &[String]) -> Option<Uuid> {
+        let mut best = None;
+        for entry in self.listed.values() {
+            if entry.has_password || entry.phase == GamePhase::Ended {
+                continue;
+            }
+            if entry.max_players.is_some_and(|max| entry.players >= max) {
+                continue;
+            }
+
+            // Games that would only let the player watch aren't a match
+            match self.find_game(entry.id) {
+                Some(game) => {
+                    let game = game.read().await;
+                    if game.can_join(None).is_err() || !game.seats_newcomers() {
+                        continue;
+                    }
+                }
+                None => continue,
+            }
+
+            let overlap = entry
+                .packs
+                .iter()
+                .filter(|pack| packs.contains(pack))
+                .count();
+            let free_slots = entry
+                .max_players
+                .map_or(usize::MAX, |max| max.saturating_sub(entry.players));
+            let score = (entry.phase == GamePhase::Waiting, overlap, free_slots);
+
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score > *best_score)
+            {
+                best = Some((score, entry.id));
+            }
+        }
+
+        best.map(|(_, id)| id)
+    }
+
+    /// Sends a client to a game if they're allowed to join it
+    async fn join_game(
+        &mut self,
+        network_handler: &mut NetworkHandler,
+        client_id: Uuid,
+        game_id: Uuid,
+        password: Option<&str>,
+    ) -> PacketResponse {
+        if !network_handler.valid_listener(game_id) {
+            return PacketResponse::RejectedWithReason("Invalid server id".to_owned());
+        };
+
+        // The password can only be checked on a game we can look at, so anything else is turned
+        // away rather than let in unchecked
+        let game = match self.find_game(game_id) {
+            Some(game) => game,
+            None =>
+                return PacketResponse::RejectedWithReason(
+                    "Couldn't find that game, try again".to_owned(),
+                ),
+        };
+        if let Err(reason) = game.read().await.can_join(password) {
+            return PacketResponse::RejectedWithReason(reason);
+        }
+
+        match network_handler.forward_client(client_id, game_id).await {
+            Some(_) => PacketResponse::Accepted,
+            None => PacketResponse::Rejected,
+        }
+    }
+
+    /// Recreates the games that were running when the server last stopped
+    pub fn restore_games(&mut self, network_handler: &mut NetworkHandler) {
+        for snapshot in snapshot::load_snapshots() {
+            let id = snapshot.id;
+            match Game::restore(snapshot, self.pack_store.clone()) {
+                Ok(mut game) => {
+                    // Snapshots from before invite codes existed won't have one
+                    if game.code.is_empty() || self.find_game_by_code(&game.code).is_some() {
+                        game.code = self.new_invite_code();
+                    }
+                    let game = Rc::new(RwLock::new(game));
+                    network_handler.add_listener_with_id(id, game.clone());
+                    self.games.push(game);
+                    info!("Restored game {}", id);
+                }
+                Err(e) => error!("Failed to restore game {}: {}", id, e),
+            }
+        }
+    }
+
+    /// Saves every running game so it can be restored after a restart
+    pub async fn save_snapshots(&mut self) {
+        self.last_snapshot = Instant::now();
+
+        let mut snapshots: Vec<GameSnapshot> = Vec::new();
+        for game in self.games.iter() {
+            let game = game.read().await;
+            if !game.is_terminated() {
+                snapshots.push(game.snapshot());
+            }
+        }
+
+        if let Err(e) = snapshot::save_snapshots(&snapshots) {
+            error!("Failed to save games: {}", e);
+        }
+    }
 
+    /// Forces every game created from now on to use the given seed, or random seeds if `None`
+    pub fn set_debug_seed(&mut self, seed: Option<u64>) {
+        self.debug_seed = seed;
+    }
+}
+
+#[async_trait(?Send)]
+impl Listener for Lobby {
+    async fn client_connected(&mut self, network_handler: &mut NetworkHandler, client_id: Uuid) {
+        self.send_server_list(network_handler, client_id).await;
+
+        let mut client_handler = network_handler.client_handler.lock().await;
         match client_handler
             .send_packet(
                 client_id,
@@ -71,13 +435,23 @@ impl Listener for Lobby {
             Some(Err(e)) => warn!("Error sending card packs to client: {}", e),
             _ => {}
         }
+
+        client_handler
+            .send_packet(client_id, &ClientBoundPacket::ChatHistory {
+                scope: ChatScope::Lobby,
+                messages: self.chat.history(),
+            })
+            .await;
     }
 
     async fn client_disconnected(
         &mut self,
         _network_handler: &mut NetworkHandler,
-        _client_id: Uuid,
+        client_id: Uuid,
     ) {
+        self.views.remove(&client_id);
+        self.names.remove(&client_id);
+        self.chat.forget(client_id);
     }
 
     async fn handle_packet(
@@ -87,62 +461,107 @@ impl Listener for Lobby {
         sender_id: Uuid,
     ) -> PacketResponse {
         match packet {
-            ServerBoundPacket::CreateServer(settings) => {
-                if settings.packs.len() == 0 {
-                    return PacketResponse::RejectedWithReason("Packs cannot be empty".to_owned());
-                }
+            ServerBoundPacket::CreateServer(settings) =>
+                self.create_game(network_handler, sender_id, settings.clone())
+                    .await,
 
-                if settings.points_to_win == 0 {
-                    return PacketResponse::RejectedWithReason(
-                        "Points to win has to be at least 1".to_owned(),
-                    );
-                }
+            ServerBoundPacket::QuickMatch { packs } => {
+                self.update_server_list(network_handler).await;
 
-                match settings.max_players {
-                    Some(p) =>
-                        if p < 2 {
-                            return PacketResponse::RejectedWithReason(
-                                "Max players needs to be at least 2".to_owned(),
-                            );
-                        },
-                    None => {}
-                }
+                match self.best_open_game(packs).await {
+                    Some(game_id) =>
+                        self.join_game(network_handler, sender_id, game_id, None)
+                            .await,
+                    None => {
+                        let known_packs = self.pack_store.read().unwrap().get_packs_meta();
+                        let mut packs = packs
+                            .iter()
+                            .filter(|pack| known_packs.iter().any(|(name, ..)| &name == pack))
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        if packs.is_empty() {
+                            packs.push(DEFAULT_PACK.to_owned());
+                        }
 
-                let new_game = match Game::new(
-                    // Use a fake Uuid to create the game because we can't know what uuid is assigned to it
-                    Uuid::from_u128(0),
-                    sender_id.clone(),
-                    self.pack_store.clone(),
-                    settings.clone(),
-                ) {
-                    Ok(g) => Rc::new(RwLock::new(g)),
-                    Err(e) => {
-                        warn!("Error making new game {}", e);
-                        return PacketResponse::RejectedWithReason(
-                            "Error creating new game".to_owned(),
-                        );
+                        self.create_game(network_handler, sender_id, default_settings(packs))
+                            .await
                     }
+                }
+            }
+
+            ServerBoundPacket::JoinGame { id, password } =>
+                self.join_game(network_handler, sender_id, *id, password.as_deref())
+                    .await,
+
+            ServerBoundPacket::JoinByCode { code, password } => {
+                let code = code.trim().to_ascii_uppercase();
+                let game_id = match self.find_game_by_code(&code) {
+                    Some(game) => game.read().await.id,
+                    None =>
+                        return PacketResponse::RejectedWithReason(format!(
+                            "No game has the code {}",
+                            code
+                        )),
                 };
 
-                let listener_id = network_handler.add_listener(new_game.clone());
-                new_game.write().await.id = listener_id;
-                self.games.push(new_game);
+                self.join_game(network_handler, sender_id, game_id, password.as_deref())
+                    .await
+            }
+
+            ServerBoundPacket::Rejoin { game_id, token } => {
+                let game = match self.find_game(*game_id) {
+                    Some(game) => game,
+                    None => return PacketResponse::RejectedWithReason("Game not found".to_owned()),
+                };
+
+                if !game.write().await.expect_rejoin(sender_id, *token) {
+                    return PacketResponse::RejectedWithReason(
+                        "Nobody is waiting to rejoin with that token".to_owned(),
+                    );
+                }
 
-                match network_handler.forward_client(sender_id, listener_id).await {
+                match network_handler.forward_client(sender_id, *game_id).await {
                     Some(_) => PacketResponse::Accepted,
                     None => PacketResponse::Rejected,
                 }
             }
 
-            ServerBoundPacket::JoinGame(server_id) => {
-                if !network_handler.valid_listener(*server_id) {
-                    return PacketResponse::RejectedWithReason("Invalid server id".to_owned());
-                };
+            ServerBoundPacket::RefreshServerList { sort, filter } => {
+                self.views.insert(sender_id, (*sort, filter.clone()));
+                self.send_server_list(network_handler, sender_id).await;
+                PacketResponse::Accepted
+            }
 
-                match network_handler.forward_client(sender_id, *server_id).await {
-                    Some(_) => PacketResponse::Accepted,
-                    None => PacketResponse::Rejected,
+            ServerBoundPacket::SetPlayerName(name) => match sanitize_name(name) {
+                Ok(name) => {
+                    self.names.insert(sender_id, name);
+                    PacketResponse::Accepted
                 }
+                Err(reason) => PacketResponse::RejectedWithReason(reason),
+            },
+
+            ServerBoundPacket::SendChat(text) => {
+                let name = self.name_of(sender_id);
+                let message = match self.chat.post(sender_id, &name, text) {
+                    Ok(message) => message,
+                    Err(reason) => return PacketResponse::RejectedWithReason(reason),
+                };
+
+                let lobby_id = *LOBBY_ID.get().unwrap();
+                network_handler
+                    .client_handler
+                    .lock()
+                    .await
+                    .broadcast(
+                        &ClientBoundPacket::Chat {
+                            scope: ChatScope::Lobby,
+                            message,
+                        },
+                        |client| client.listener == lobby_id,
+                        |client| warn!("Error sending chat message to {}", client.id),
+                    )
+                    .await;
+                PacketResponse::Accepted
             }
 
             ServerBoundPacket::RequestCardPacks => {
@@ -162,7 +581,85 @@ impl Listener for Lobby {
         }
     }
 
+    async fn tick(&mut self, network_handler: &mut NetworkHandler) {
+        self.update_server_list(network_handler).await;
+
+        if self.last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+            self.save_snapshots().await;
+        }
+    }
+
     fn is_terminated(&self) -> bool {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::pack_store;
+
+    /// Adds a game to the lobby which the server list shows with the given details
+    fn add_game(lobby: &mut Lobby, phase: GamePhase, packs: &[&str], players: usize) -> Uuid {
+        let id = Uuid::new_v4();
+        let settings = default_settings(vec![DEFAULT_PACK.to_owned()]);
+        let game = Game::new(id, Uuid::new_v4(), lobby.pack_store.clone(), settings).unwrap();
+        lobby.games.push(Rc::new(RwLock::new(game)));
+        lobby.listed.insert(id, ServerListEntry {
+            id,
+            title: String::new(),
+            host_name: String::new(),
+            players,
+            max_players: Some(8),
+            spectators: 0,
+            phase,
+            round: 0,
+            points_to_win: 10,
+            packs: packs.iter().map(|pack| pack.to_string()).collect(),
+            has_password: false,
+        });
+        id
+    }
+
+    #[tokio::test]
+    async fn quick_match_prefers_games_that_havent_started() {
+        let mut lobby = Lobby::new(pack_store(20, 100));
+        add_game(&mut lobby, GamePhase::Playing, &["Red", "Blue"], 1);
+        let waiting = add_game(&mut lobby, GamePhase::Waiting, &[], 6);
+
+        let packs = ["Red".to_owned(), "Blue".to_owned()];
+        assert_eq!(lobby.best_open_game(&packs).await, Some(waiting));
+    }
+
+    #[tokio::test]
+    async fn quick_match_prefers_shared_packs_then_room() {
+        let mut lobby = Lobby::new(pack_store(20, 100));
+        add_game(&mut lobby, GamePhase::Waiting, &["Blue"], 1);
+        add_game(&mut lobby, GamePhase::Waiting, &["Red"], 6);
+        let roomiest = add_game(&mut lobby, GamePhase::Waiting, &["Red"], 2);
+
+        assert_eq!(
+            lobby.best_open_game(&["Red".to_owned()]).await,
+            Some(roomiest)
+        );
+    }
+
+    #[tokio::test]
+    async fn quick_match_skips_private_and_ended_games() {
+        let mut lobby = Lobby::new(pack_store(20, 100));
+        let locked = add_game(&mut lobby, GamePhase::Waiting, &[], 1);
+        lobby.listed.get_mut(&locked).unwrap().has_password = true;
+        add_game(&mut lobby, GamePhase::Ended, &[], 1);
+
+        assert_eq!(lobby.best_open_game(&[]).await, None);
+    }
+
+    #[tokio::test]
+    async fn quick_match_skips_full_games() {
+        let mut lobby = Lobby::new(pack_store(20, 100));
+        add_game(&mut lobby, GamePhase::Waiting, &["Red"], 8);
+        let open = add_game(&mut lobby, GamePhase::Waiting, &[], 7);
+
+        assert_eq!(lobby.best_open_game(&["Red".to_owned()]).await, Some(open));
+    }
+}
@@ -1,6 +1,16 @@
+pub mod bots;
+mod chat;
+mod deck;
+pub mod event_log;
 mod game;
 mod lobby;
 pub mod packs;
+mod password;
+mod rate_limit;
+mod settings;
+mod snapshot;
+#[cfg(test)]
+mod test_util;
 
 pub use game::*;
 pub use lobby::*;
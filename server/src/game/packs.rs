@@ -10,7 +10,7 @@ use std::{
     sync::Arc,
 };
 
-const DEFAULT_PACK: &str = "CAH Base Set";
+pub const DEFAULT_PACK: &str = "CAH Base Set";
 const DEFAULT_PACK_JSON: &str = "CAH Base Set.json";
 
 /// A store to manage loading and unloading [Packs](Pack)
@@ -142,6 +142,45 @@ impl PackStore {
         }
     }
 
+    /// Appends responses to a custom pack, creating the pack if it doesn't exist yet
+    pub fn add_responses(
+        &mut self,
+        pack_name: &str,
+        responses: Vec<Response>,
+    ) -> Result<(), String> {
+        let file_name = format!("{}.json", pack_name);
+        let mut pack = match self.possible_packs.get(&file_name) {
+            Some((true, ..)) => return Err(format!("Pack {} is an official pack", pack_name)),
+            Some(_) => Self::read_pack(&self.custom_dir().join(&file_name))?,
+            None => Pack {
+                name: pack_name.to_owned(),
+                official: false,
+                responses: Vec::new(),
+                prompts: Vec::new(),
+            },
+        };
+
+        // The same write-in can win in many games, but it only needs to be saved once
+        for response in responses {
+            if !pack.responses.contains(&response) {
+                pack.responses.push(response);
+            }
+        }
+
+        // Games share the loaded copy of a pack and unloading counts them, so the copy can only
+        // be dropped when no game uses it. Otherwise new games see the saved responses once
+        // every game using the old copy ends and the pack is unloaded.
+        if self
+            .loaded_packs
+            .get(&file_name)
+            .is_some_and(|loaded| Arc::strong_count(loaded) == 1)
+        {
+            self.loaded_packs.remove(&file_name);
+        }
+
+        self.create_pack(pack)
+    }
+
     pub fn get_packs_meta(&self) -> Vec<(String, usize, usize)> {
         self.possible_packs
             .iter()
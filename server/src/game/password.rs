@@ -0,0 +1,48 @@
+use uuid::Uuid;
+
+/// A game's password. It's only ever kept in memory, so it's never sent to players or saved.
+pub struct Password(String);
+
+impl Password {
+    pub fn new(password: &str) -> Self {
+        Password(password.to_owned())
+    }
+
+    /// A password nobody knows, for games whose password was lost in a restart. Newcomers can't
+    /// join until the host sets a new one.
+    pub fn locked() -> Self {
+        Password(Uuid::new_v4().to_string())
+    }
+
+    /// Whether the given password is this one, taking the same time wherever they differ
+    pub fn matches(&self, password: &str) -> bool {
+        let (expected, given) = (self.0.as_bytes(), password.as_bytes());
+        expected.len() == given.len()
+            && expected
+                .iter()
+                .zip(given)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_only_the_same_password() {
+        let password = Password::new("hunter2");
+        assert!(password.matches("hunter2"));
+        assert!(!password.matches("hunter3"));
+        assert!(!password.matches("hunter"));
+        assert!(!password.matches(""));
+    }
+
+    #[test]
+    fn locked_passwords_match_nothing_obvious() {
+        let password = Password::locked();
+        assert!(!password.matches(""));
+        assert!(!password.matches("hunter2"));
+    }
+}
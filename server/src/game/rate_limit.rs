@@ -0,0 +1,98 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+/// Limits how many times each client can do something within a window of time
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    // When each client acted, for the actions that still count towards their limit
+    recent: HashMap<Uuid, VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        RateLimiter {
+            limit,
+            window,
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Counts an action by the client, returning false without counting it if they're over
+    /// their limit
+    pub fn allow(&mut self, id: Uuid) -> bool {
+        let now = Instant::now();
+        let window = self.window;
+        let recent = self.recent.entry(id).or_default();
+        while recent
+            .front()
+            .is_some_and(|&sent| now.duration_since(sent) > window)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= self.limit {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+
+    /// Carries a client's recent actions over to their new connection
+    pub fn rekey(&mut self, old_id: Uuid, new_id: Uuid) {
+        if let Some(recent) = self.recent.remove(&old_id) {
+            self.recent.insert(new_id, recent);
+        }
+    }
+
+    pub fn forget(&mut self, id: Uuid) {
+        self.recent.remove(&id);
+    }
+
+    pub fn retain(&mut self, mut present: impl FnMut(Uuid) -> bool) {
+        self.recent.retain(|&id, _| present(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn limits_each_client_separately() {
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(limiter.allow(first));
+        assert!(limiter.allow(first));
+        assert!(!limiter.allow(first));
+        assert!(limiter.allow(second));
+    }
+
+    #[test]
+    fn old_actions_stop_counting() {
+        let mut limiter = RateLimiter::new(1, Duration::from_millis(20));
+        let id = Uuid::new_v4();
+
+        assert!(limiter.allow(id));
+        assert!(!limiter.allow(id));
+        sleep(Duration::from_millis(30));
+        assert!(limiter.allow(id));
+    }
+
+    #[test]
+    fn rekeyed_clients_keep_their_count() {
+        let mut limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let (old_id, new_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(limiter.allow(old_id));
+        limiter.rekey(old_id, new_id);
+        assert!(!limiter.allow(new_id));
+
+        limiter.forget(new_id);
+        assert!(limiter.allow(new_id));
+    }
+}
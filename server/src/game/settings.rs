@@ -0,0 +1,122 @@
+use common::protocol::{
+    CzarRotation,
+    EndCondition,
+    GameSetting,
+    GameSettings,
+    JudgingMode,
+    LateJoin,
+    TieBreak,
+};
+
+/// The most blank cards a game can shuffle into its deck
+pub const MAX_BLANK_CARDS: usize = 50;
+/// The smallest hand size, which is enough to answer any prompt
+const MIN_HAND_SIZE: usize = 3;
+/// The largest hand size, so hands still fit on screen
+const MAX_HAND_SIZE: usize = 20;
+/// The longest a game can stay paused, in minutes
+const MAX_PAUSE_TIMEOUT: u32 = 30;
+/// The most deadlines a player can be allowed to miss before they're marked AFK
+const MAX_AFK_THRESHOLD: u32 = 10;
+/// The longest password a game can have
+const MAX_PASSWORD_LENGTH: usize = 64;
+/// The longest title a game can have in the server list
+const MAX_TITLE_LENGTH: usize = 40;
+
+/// The settings used for games that are made for players instead of by them, like in quick match
+pub fn default_settings(packs: Vec<String>) -> GameSettings {
+    GameSettings {
+        max_players: Some(15),
+        max_selection_time: None,
+        points_to_win: 10,
+        packs,
+        blank_cards: 0,
+        save_write_ins: false,
+        rebooting_the_universe: false,
+        packing_heat: false,
+        judging_mode: JudgingMode::Czar,
+        vote_tie_break: TieBreak::Random,
+        hand_size: 10,
+        end_condition: EndCondition::Points,
+        end_tie_break: TieBreak::AllWin,
+        czar_rotation: CzarRotation::Sequential,
+        pause_timeout: 5,
+        skip_vote_threshold: Some(50),
+        afk_threshold: 2,
+        kick_afk: false,
+        late_join: LateJoin::Allow,
+        late_join_handicap: false,
+        password: None,
+        unlisted: false,
+        title: String::new(),
+        seed: None,
+    }
+}
+
+/// Checks every setting a new game is created with
+pub fn validate_settings(settings: &GameSettings) -> Result<(), String> {
+    if settings.packs.is_empty() {
+        return Err("Packs cannot be empty".to_owned());
+    }
+
+    validate_setting(&GameSetting::MaxPlayers(settings.max_players))?;
+    validate_setting(&GameSetting::PointsToWin(settings.points_to_win))?;
+    validate_setting(&GameSetting::BlankCards(settings.blank_cards))?;
+    validate_setting(&GameSetting::HandSize(settings.hand_size))?;
+    validate_setting(&GameSetting::EndCondition(settings.end_condition))?;
+    validate_setting(&GameSetting::PauseTimeout(settings.pause_timeout))?;
+    validate_setting(&GameSetting::SkipVoteThreshold(
+        settings.skip_vote_threshold,
+    ))?;
+    validate_setting(&GameSetting::AfkThreshold(settings.afk_threshold))?;
+    validate_setting(&GameSetting::Password(settings.password.clone()))?;
+    validate_setting(&GameSetting::Title(settings.title.clone()))
+}
+
+/// Checks a single setting, returning the reason it was rejected if it's invalid
+pub fn validate_setting(setting: &GameSetting) -> Result<(), String> {
+    match setting {
+        &GameSetting::MaxPlayers(Some(limit)) if limit < 2 =>
+            return Err("Max players needs to be at least 2".to_owned()),
+        &GameSetting::PointsToWin(0) => return Err("Points to win has to be at least 1".to_owned()),
+        &GameSetting::BlankCards(count) if count > MAX_BLANK_CARDS =>
+            return Err(format!("Blank cards cannot exceed {}", MAX_BLANK_CARDS)),
+        &GameSetting::HandSize(size) if !(MIN_HAND_SIZE ..= MAX_HAND_SIZE).contains(&size) =>
+            return Err(format!(
+                "Hand size has to be between {} and {}",
+                MIN_HAND_SIZE, MAX_HAND_SIZE
+            )),
+        GameSetting::EndCondition(EndCondition::Rounds(0)) =>
+            return Err("Number of rounds has to be at least 1".to_owned()),
+        GameSetting::EndCondition(EndCondition::TimeLimit(0)) =>
+            return Err("Time limit has to be at least 1 minute".to_owned()),
+        &GameSetting::PauseTimeout(minutes) if !(1 ..= MAX_PAUSE_TIMEOUT).contains(&minutes) =>
+            return Err(format!(
+                "Pause timeout has to be between 1 and {} minutes",
+                MAX_PAUSE_TIMEOUT
+            )),
+        &GameSetting::SkipVoteThreshold(Some(percent)) if !(1 ..= 100).contains(&percent) =>
+            return Err("Skip vote threshold has to be between 1% and 100%".to_owned()),
+        GameSetting::Password(Some(password))
+            if !(1 ..= MAX_PASSWORD_LENGTH).contains(&password.chars().count()) =>
+            return Err(format!(
+                "Passwords have to be between 1 and {} characters",
+                MAX_PASSWORD_LENGTH
+            )),
+        GameSetting::Title(title) if title.trim().chars().count() > MAX_TITLE_LENGTH =>
+            return Err(format!(
+                "Titles cannot be longer than {} characters",
+                MAX_TITLE_LENGTH
+            )),
+        GameSetting::Title(title) if title.chars().any(char::is_control) =>
+            return Err("Titles cannot contain control characters".to_owned()),
+        &GameSetting::AfkThreshold(missed) if !(1 ..= MAX_AFK_THRESHOLD).contains(&missed) =>
+            return Err(format!(
+                "AFK threshold has to be between 1 and {} missed deadlines",
+                MAX_AFK_THRESHOLD
+            )),
+        _ => {}
+    }
+
+    Ok(())
+}
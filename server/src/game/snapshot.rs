@@ -0,0 +1,125 @@
+use super::{
+    deck::Deck,
+    event_log::EventLog,
+    game::{GameState, Player},
+};
+use common::{
+    data::cards::{CardID, Prompt},
+    protocol::{clientbound::RoundRecord, BotKind, GameSettings},
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use uuid::Uuid;
+
+/// Where running games are saved so they survive a server restart
+pub const SNAPSHOT_DIR: &str = "./snapshots";
+
+/// Everything needed to recreate a running game
+#[derive(Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub id: Uuid,
+    pub code: String,
+    pub host_id: Uuid,
+    // Packs are reloaded by name when the game is restored. The password is left out of these.
+    pub settings: GameSettings,
+    pub players: Vec<(Uuid, Player)>,
+    pub bots: Vec<(Uuid, BotKind)>,
+    pub prompts: Deck,
+    pub responses: Deck,
+    pub state: GameState,
+    pub czar_index: usize,
+    pub last_czar: Option<Uuid>,
+    pub past_czars: HashSet<Uuid>,
+    pub round_winners: Vec<Uuid>,
+    pub round: u32,
+    // Instants can't be saved, so these are stored as the time elapsed since then
+    pub millis_since_start: Option<u64>,
+    pub millis_since_round_start: Option<u64>,
+    pub millis_since_pause: Option<u64>,
+    pub history: Vec<RoundRecord>,
+    pub votes: HashMap<Uuid, Uuid>,
+    pub skip_votes: HashSet<Uuid>,
+    pub reactions: HashMap<Uuid, u32>,
+    pub prompt_redrawn: bool,
+    pub current_prompt: Option<Prompt>,
+    pub current_prompt_card: Option<CardID>,
+    pub event_log: EventLog,
+    // How far into the seed's sequence of random numbers the game has gotten
+    pub rng_word_pos: u128,
+    // Restored games that had a password stay locked until the host sets a new one
+    pub has_password: bool,
+}
+
+/// Writes a snapshot of every running game, removing snapshots of games which have ended
+pub fn save_snapshots(snapshots: &[GameSnapshot]) -> Result<(), String> {
+    fs::create_dir_all(SNAPSHOT_DIR)
+        .map_err(|e| format!("Error creating snapshot directory: {}", e))?;
+
+    for snapshot in snapshots {
+        let json = serde_json::to_string(snapshot)
+            .map_err(|e| format!("Error serializing game {}: {}", snapshot.id, e))?;
+        fs::write(snapshot_path(snapshot.id), json)
+            .map_err(|e| format!("Error writing snapshot of game {}: {}", snapshot.id, e))?;
+    }
+
+    for (id, path) in saved_snapshots() {
+        if !snapshots.iter().any(|snapshot| snapshot.id == id) {
+            if let Err(e) = fs::remove_file(path) {
+                error!("Failed to remove old snapshot of game {}: {}", id, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads every saved snapshot, skipping any that can't be read
+pub fn load_snapshots() -> Vec<GameSnapshot> {
+    saved_snapshots()
+        .into_iter()
+        .filter_map(|(id, path)| {
+            let json = match fs::read_to_string(path) {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Failed to read snapshot of game {}: {}", id, e);
+                    return None;
+                }
+            };
+
+            match serde_json::from_str(&json) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    error!("Failed to deserialize snapshot of game {}: {}", id, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn saved_snapshots() -> Vec<(Uuid, PathBuf)> {
+    let entries = match fs::read_dir(SNAPSHOT_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            let id = Uuid::parse_str(path.file_stem()?.to_str()?).ok()?;
+            Some((id, path))
+        })
+        .collect()
+}
+
+fn snapshot_path(game_id: Uuid) -> PathBuf {
+    Path::new(SNAPSHOT_DIR).join(format!("{}.json", game_id))
+}
@@ -0,0 +1,57 @@
+use super::packs::{PackStore, DEFAULT_PACK};
+use crate::network::{client::ClientHandler, NetworkHandler};
+use common::data::cards::{Pack, Prompt};
+use futures::channel::oneshot;
+use std::{
+    fs,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Creates a pack store with a default pack holding the given number of prompts and responses.
+/// The pack is written to a temporary directory which is removed again once it's loaded.
+pub fn pack_store(prompts: usize, responses: usize) -> Arc<RwLock<PackStore>> {
+    let pack_dir = std::env::temp_dir().join(format!("cfh-test-{}", Uuid::new_v4()));
+    fs::create_dir_all(pack_dir.join("official")).unwrap();
+    fs::create_dir_all(pack_dir.join("custom")).unwrap();
+
+    let pack = Pack {
+        name: DEFAULT_PACK.to_owned(),
+        official: true,
+        responses: (0 .. responses)
+            .map(|index| format!("Response {}", index))
+            .collect(),
+        prompts: (0 .. prompts)
+            .map(|index| Prompt {
+                text: format!("Prompt {} _", index),
+                pick: 1,
+                draw: None,
+            })
+            .collect(),
+    };
+    fs::write(
+        pack_dir
+            .join("official")
+            .join(format!("{}.json", DEFAULT_PACK)),
+        serde_json::to_string(&pack).unwrap(),
+    )
+    .unwrap();
+
+    // The default pack is never unloaded, so the store doesn't need its files after this
+    let pack_store = PackStore::new(&pack_dir);
+    fs::remove_dir_all(&pack_dir).unwrap();
+    Arc::new(RwLock::new(pack_store.unwrap()))
+}
+
+/// Creates a network handler with no clients connected, so every packet sent through it is
+/// dropped
+pub fn network_handler() -> NetworkHandler {
+    let (client_handler, incoming_messages) = ClientHandler::new();
+    let (shutdown_hook, _) = oneshot::channel();
+    NetworkHandler::new(
+        Arc::new(Mutex::new(client_handler)),
+        incoming_messages,
+        shutdown_hook,
+    )
+}
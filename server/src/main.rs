@@ -2,14 +2,14 @@ mod game;
 mod logging;
 mod network;
 
-use common::data::cards::Pack;
+use common::data::{cards::Pack, events::ReplayState};
 use futures::{
     channel::oneshot::{self, Sender},
     TryFutureExt,
 };
-use game::packs::PackStore;
+use game::{event_log, packs::PackStore};
 use linefeed::{Interface, ReadResult};
-use log::error;
+use log::{error, info};
 use network::{client::ClientHandler, NetworkHandler};
 use once_cell::sync::OnceCell;
 use std::{
@@ -26,9 +26,10 @@ use std::{
 use tokio::sync::{
     mpsc::{self, channel},
     Mutex,
+    RwLock as AsyncRwLock,
 };
 use uuid::Uuid;
-use warp::{ws::Ws, Filter};
+use warp::{http::StatusCode, reply, ws::Ws, Filter};
 use zip::ZipArchive;
 
 const CLIENT_FILES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/client.zip"));
@@ -66,8 +67,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let server_shutdown_hook = start_server(client_handler.clone(), pack_store.clone()).await;
     let mut network_handler =
         NetworkHandler::new(client_handler, incoming_messages, server_shutdown_hook);
-    let lobby_id = network_handler.add_listener(game::Lobby::new(pack_store.clone()));
+    let lobby = Rc::new(AsyncRwLock::new(game::Lobby::new(pack_store.clone())));
+    let lobby_id = network_handler.add_listener(lobby.clone());
     LOBBY_ID.set(lobby_id).expect("Error setting LOBBY_ID");
+    lobby.write().await.restore_games(&mut network_handler);
 
     loop {
         // Check for a new command every 50ms
@@ -80,6 +83,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         break;
                     }
 
+                    // Usage: seed <number|random>
+                    if let Some(seed) = command.strip_prefix("seed ") {
+                        match seed.trim() {
+                            "random" => {
+                                lobby.write().await.set_debug_seed(None);
+                                info!("New games will use random seeds");
+                            }
+                            seed => match seed.parse::<u64>() {
+                                Ok(seed) => {
+                                    lobby.write().await.set_debug_seed(Some(seed));
+                                    info!("New games will use seed {}", seed);
+                                }
+                                Err(_) => error!("Invalid seed: {}", seed),
+                            },
+                        }
+                    }
+
                     // TODO: handle other commands
                 }
                 _ => {}
@@ -88,8 +108,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
 
         network_handler.handle_messages().await;
+        network_handler.tick().await;
     }
 
+    lobby.write().await.save_snapshots().await;
     network_handler.shutdown().await;
 
     // Move off of the command prompt
@@ -173,12 +195,46 @@ async fn start_server(
         .unwrap()
     });
 
+    // Past games are served read-only from their saved event logs
+    let history_list =
+        warp::path!("history").map(|| serde_json::to_string(&event_log::saved_games()).unwrap());
+    // Missing, unreadable and unlisted games all look the same from outside
+    let history =
+        warp::path!("history" / Uuid).map(|game_id: Uuid| match event_log::load_log(game_id) {
+            Ok(events) =>
+                reply::with_status(serde_json::to_string(&events).unwrap(), StatusCode::OK),
+            Err(e) => {
+                info!("Couldn't serve the log of game {}: {}", game_id, e);
+                reply::with_status("Game not found".to_owned(), StatusCode::NOT_FOUND)
+            }
+        });
+    let replay = warp::path!("history" / Uuid / usize).map(|game_id: Uuid, count: usize| {
+        match event_log::load_log(game_id) {
+            Ok(events) => reply::with_status(
+                serde_json::to_string(&ReplayState::replay(&events, count)).unwrap(),
+                StatusCode::OK,
+            ),
+            Err(e) => {
+                info!("Couldn't serve the log of game {}: {}", game_id, e);
+                reply::with_status("Game not found".to_owned(), StatusCode::NOT_FOUND)
+            }
+        }
+    });
+
     let (shutdown_hook, rx) = oneshot::channel::<()>();
 
-    let (_addr, server) = warp::serve(www.or(upload).or(ws_server).or(search_pack).or(list))
-        .bind_with_graceful_shutdown(([0, 0, 0, 0], 25565), async {
-            rx.await.ok();
-        });
+    let (_addr, server) = warp::serve(
+        www.or(upload)
+            .or(ws_server)
+            .or(search_pack)
+            .or(list)
+            .or(history_list)
+            .or(history)
+            .or(replay),
+    )
+    .bind_with_graceful_shutdown(([0, 0, 0, 0], 25565), async {
+        rx.await.ok();
+    });
 
     tokio::task::spawn(server);
     shutdown_hook
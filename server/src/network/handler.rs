@@ -192,9 +192,25 @@ impl NetworkHandler {
 
     pub fn add_listener<L: Listener + 'static>(&mut self, listener: L) -> Uuid {
         let id = Uuid::new_v4();
+        self.add_listener_with_id(id, listener);
+        id
+    }
+
+    /// Adds a listener under a known id, such as a game restored from a snapshot
+    pub fn add_listener_with_id<L: Listener + 'static>(&mut self, id: Uuid, listener: L) {
         self.listeners
             .insert(id, Rc::new(RefCell::new(Box::new(listener))));
-        id
+    }
+
+    /// Lets every listener handle anything time based
+    pub async fn tick(&mut self) {
+        let listeners = self.listeners.values().cloned().collect::<Vec<_>>();
+        for listener in listeners {
+            listener.borrow_mut().tick(self).await;
+        }
+
+        self.listeners
+            .retain(|_, listener| !listener.borrow().is_terminated());
     }
 
     pub fn valid_listener(&self, id: Uuid) -> bool {
@@ -249,6 +265,9 @@ pub trait Listener {
         sender_id: Uuid,
     ) -> PacketResponse;
 
+    /// Called regularly from the main loop
+    async fn tick(&mut self, _network_handler: &mut NetworkHandler) {}
+
     fn is_terminated(&self) -> bool {
         false
     }
@@ -282,6 +301,10 @@ impl<T: Listener> Listener for Rc<RwLock<T>> {
             .await
     }
 
+    async fn tick(&mut self, network_handler: &mut NetworkHandler) {
+        self.write().await.tick(network_handler).await
+    }
+
     fn is_terminated(&self) -> bool {
         // If the RwLock is blocked this will not execute properly
         // But terminated will mean no clients blocking the RwLock